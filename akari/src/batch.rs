@@ -0,0 +1,175 @@
+use std::{
+    collections::{ HashMap, VecDeque },
+    io,
+    panic,
+    sync::{ Condvar, Mutex },
+    thread,
+};
+
+use super::{ Client, ClientBuilder, Url };
+
+const DEFAULT_PARALLELISM: usize = 4;
+const DEFAULT_HOST_LIMIT: usize = 2;
+
+pub struct Batch {
+    builder: ClientBuilder,
+    parallelism: usize,
+    host_limit: usize,
+}
+
+struct Queue<'u> {
+    pending: VecDeque<Job<'u>>,
+    active: HashMap<String, usize>,
+}
+
+struct Job<'u> {
+    index: usize,
+    url: &'u str,
+    host: Option<String>,
+}
+
+// releases the host of a job once handled, even if the handler panics, so that other workers waiting on it are not left blocked
+struct Slot<'q, 'u> {
+    queue: &'q Mutex<Queue<'u>>,
+    available: &'q Condvar,
+    host: Option<String>,
+}
+
+impl Batch {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new(builder: ClientBuilder) -> Self {
+        Self {
+            builder,
+            parallelism: DEFAULT_PARALLELISM,
+            host_limit: DEFAULT_HOST_LIMIT,
+        }
+    }
+    
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+    
+    pub fn with_host_limit(mut self, host_limit: usize) -> Self {
+        self.host_limit = host_limit.max(1);
+        self
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    pub fn run<T, F>(&self, urls: &[&str], handler: F) -> Vec<io::Result<T>>
+    where
+        T: Send,
+        F: Fn(&mut Client, &str) -> io::Result<T> + Sync,
+    {
+        let pending = urls.iter()
+            .enumerate()
+            .map(|(index, &url)| Job {
+                index,
+                url,
+                // unparsable addresses are not limited, as they fail right away
+                host: Url::parse(url).ok().map(|url| format!("{}:{}", url.host(), url.port().unwrap_or_default())),
+            })
+            .collect();
+        
+        let queue = Mutex::new(Queue {
+            pending,
+            active: HashMap::new(),
+        });
+        
+        let available = Condvar::new();
+        
+        let mut results: Vec<(usize, io::Result<T>)> = thread::scope(|scope| {
+            
+            let workers: Vec<_> = (0..self.parallelism.min(urls.len()))
+                .map(|_| scope.spawn(|| self.work(&queue, &available, &handler)))
+                .collect();
+            
+            workers.into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|error| panic::resume_unwind(error)))
+                .collect()
+            
+        });
+        
+        results.sort_by_key(|(index, _)| *index);
+        
+        results.into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+    
+    fn work<T, F>(&self, queue: &Mutex<Queue>, available: &Condvar, handler: &F) -> Vec<(usize, io::Result<T>)>
+    where
+        F: Fn(&mut Client, &str) -> io::Result<T>,
+    {
+        // clients cannot be shared between threads, so every worker builds its own
+        let mut client = self.builder.clone().build();
+        let mut results = Vec::new();
+        
+        while let Some(mut job) = self.next(queue, available) {
+            
+            let _slot = Slot {
+                queue,
+                available,
+                host: job.host.take(),
+            };
+            
+            let result = match &mut client {
+                Ok(client) => handler(client, job.url),
+                Err(error) => Err(io::Error::new(error.kind(), error.to_string())),
+            };
+            
+            results.push((job.index, result));
+            
+        }
+        
+        results
+    }
+    
+    fn next<'u>(&self, queue: &Mutex<Queue<'u>>, available: &Condvar) -> Option<Job<'u>> {
+        let mut queue = queue.lock().unwrap_or_else(|error| error.into_inner());
+        
+        loop {
+            
+            if queue.pending.is_empty() {
+                return None;
+            }
+            
+            // the earliest job whose host is below the limit goes first
+            let position = queue.pending.iter().position(|job| match &job.host {
+                Some(host) => queue.active.get(host).copied().unwrap_or_default() < self.host_limit,
+                None => true,
+            });
+            
+            if let Some(job) = position.and_then(|position| queue.pending.remove(position)) {
+                if let Some(host) = &job.host {
+                    *queue.active.entry(host.clone()).or_default() += 1;
+                }
+                return Some(job);
+            }
+            
+            queue = available.wait(queue).unwrap_or_else(|error| error.into_inner());
+            
+        }
+    }
+    
+}
+
+impl Drop for Slot<'_, '_> {
+    
+    fn drop(&mut self) {
+        if let Some(host) = &self.host {
+            let mut queue = self.queue.lock().unwrap_or_else(|error| error.into_inner());
+            if let Some(active) = queue.active.get_mut(host) {
+                *active -= 1;
+            }
+            self.available.notify_all();
+        }
+    }
+    
+}
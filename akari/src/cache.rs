@@ -0,0 +1,132 @@
+use std::{
+    collections::HashMap,
+    fs::{ self, File },
+    io::{ self, Write },
+    path::PathBuf,
+};
+
+pub struct Cache {
+    path: PathBuf,
+    entries: HashMap<String, Validators>,
+}
+
+#[derive(Clone, Default)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Cache {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn load<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        
+        // a missing file is equivalent to an empty cache
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+        
+        let entries = content.lines()
+            .filter_map(|line| {
+                
+                let mut fields = line.split('\t');
+                
+                let url = fields.next().filter(|url| ! url.is_empty())?;
+                let etag = fields.next().filter(|etag| ! etag.is_empty());
+                let last_modified = fields.next().filter(|last_modified| ! last_modified.is_empty());
+                
+                Some((url.to_owned(), Validators {
+                    etag: etag.map(str::to_owned),
+                    last_modified: last_modified.map(str::to_owned),
+                }))
+                
+            })
+            .collect();
+        
+        Ok(Self {
+            path,
+            entries,
+        })
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn get(&self, url: &str) -> Option<&Validators> {
+        self.entries.get(url)
+    }
+    
+    
+    // -------------------- mutators --------------------
+    
+    
+    pub fn insert(&mut self, url: &str, validators: Validators) -> io::Result<()> {
+        if validators.is_empty() {
+            self.entries.remove(url);
+        } else {
+            self.entries.insert(url.to_owned(), validators);
+        }
+        
+        self.commit()
+    }
+    
+    fn commit(&self) -> io::Result<()> {
+        let mut content = String::new();
+        
+        for (url, validators) in &self.entries {
+            content.push_str(url);
+            content.push('\t');
+            content.push_str(validators.etag.as_deref().unwrap_or_default());
+            content.push('\t');
+            content.push_str(validators.last_modified.as_deref().unwrap_or_default());
+            content.push('\n');
+        }
+        
+        let tmp_path = chikuwa::EphemeralPath::builder()
+            .with_base(self.path.parent().ok_or(io::Error::new(io::ErrorKind::InvalidInput, "Invalid path"))?)
+            .with_suffix(".tmp")
+            .build();
+        
+        File::create(&tmp_path)?.write_all(content.as_bytes())?;
+        
+        // attempt to perform the update atomically
+        fs::rename(&tmp_path, &self.path)?;
+        
+        tmp_path.unmanage();
+        
+        Ok(())
+    }
+    
+}
+
+impl Validators {
+    
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+    
+    pub(crate) fn headers(&self) -> String {
+        let mut headers = String::new();
+        
+        if let Some(etag) = &self.etag {
+            headers.push_str("If-None-Match: ");
+            headers.push_str(etag);
+            headers.push_str("\r\n");
+        }
+        
+        if let Some(last_modified) = &self.last_modified {
+            headers.push_str("If-Modified-Since: ");
+            headers.push_str(last_modified);
+            headers.push_str("\r\n");
+        }
+        
+        headers
+    }
+    
+}
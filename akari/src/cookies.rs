@@ -0,0 +1,361 @@
+use std::{
+    cmp::Reverse,
+    fs::{ self, File },
+    io::{ self, Write },
+    path::PathBuf,
+    sync::{ Arc, Mutex, MutexGuard },
+    time::{ SystemTime, UNIX_EPOCH },
+};
+
+use super::Url;
+
+// https://www.rfc-editor.org/rfc/rfc6265
+
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+// cloning shares the cookies, so that every client built from the same configuration sees them
+#[derive(Clone)]
+pub struct CookieJar {
+    path: PathBuf,
+    cookies: Arc<Mutex<Vec<Cookie>>>,
+}
+
+#[derive(Clone)]
+struct Cookie {
+    domain: String,
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    // zero for cookies lasting only until the end of the session
+    expires: u64,
+    name: String,
+    value: String,
+}
+
+impl CookieJar {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn load<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        
+        // a missing file is equivalent to an empty jar
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+        
+        let now = now();
+        
+        // "domain, include subdomains, path, secure, expires, name, value" in the netscape format
+        let cookies = content.lines()
+            .filter_map(|line| {
+                
+                let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+                    Some(line) => (line, true),
+                    None => (line, false),
+                };
+                
+                if line.starts_with('#') {
+                    return None;
+                }
+                
+                let mut fields = line.split('\t');
+                
+                let domain = fields.next().filter(|domain| ! domain.is_empty())?;
+                let include_subdomains = fields.next()?.eq_ignore_ascii_case("TRUE");
+                let path = fields.next()?;
+                let secure = fields.next()?.eq_ignore_ascii_case("TRUE");
+                let expires = fields.next()?.trim().parse().ok()?;
+                let name = fields.next()?;
+                let value = fields.next().unwrap_or_default();
+                
+                Some(Cookie {
+                    domain: domain.trim_start_matches('.').to_ascii_lowercase(),
+                    include_subdomains,
+                    path: path.to_owned(),
+                    secure,
+                    http_only,
+                    expires,
+                    name: name.to_owned(),
+                    value: value.to_owned(),
+                })
+                
+            })
+            .filter(|cookie| ! cookie.expired(now))
+            .collect();
+        
+        Ok(Self {
+            path,
+            cookies: Arc::new(Mutex::new(cookies)),
+        })
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+    
+    pub(crate) fn header(&self, url: &Url) -> Option<String> {
+        let now = now();
+        let host = url.connect_host();
+        let path = url.path();
+        
+        let mut matching: Vec<Cookie> = self.lock().iter()
+            .filter(|cookie| ! cookie.expired(now))
+            .filter(|cookie| ! cookie.secure || url.is_secure())
+            .filter(|cookie| cookie.domain_matches(host) && cookie.path_matches(path))
+            .cloned()
+            .collect();
+        
+        if matching.is_empty() {
+            return None;
+        }
+        
+        // cookies with longer paths are listed first
+        matching.sort_by_key(|cookie| Reverse(cookie.path.len()));
+        
+        let pairs: Vec<String> = matching.iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect();
+        
+        Some(format!("Cookie: {}\r\n", pairs.join("; ")))
+    }
+    
+    
+    // -------------------- mutators --------------------
+    
+    
+    pub fn save(&self) -> io::Result<()> {
+        let mut content = String::from("# Netscape HTTP Cookie File\n");
+        
+        for cookie in self.lock().iter() {
+            if cookie.http_only {
+                content.push_str(HTTP_ONLY_PREFIX);
+            }
+            content.push_str(&format!(
+                "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                if cookie.include_subdomains { "." } else { "" },
+                cookie.domain,
+                if cookie.include_subdomains { "TRUE" } else { "FALSE" },
+                cookie.path,
+                if cookie.secure { "TRUE" } else { "FALSE" },
+                cookie.expires,
+                cookie.name,
+                cookie.value,
+            ));
+        }
+        
+        let tmp_path = chikuwa::EphemeralPath::builder()
+            .with_base(self.path.parent().ok_or(io::Error::new(io::ErrorKind::InvalidInput, "Invalid path"))?)
+            .with_suffix(".tmp")
+            .build();
+        
+        File::create(&tmp_path)?.write_all(content.as_bytes())?;
+        
+        // attempt to perform the update atomically
+        fs::rename(&tmp_path, &self.path)?;
+        
+        tmp_path.unmanage();
+        
+        Ok(())
+    }
+    
+    pub(crate) fn capture(&self, url: &Url, set_cookie: &str) {
+        let Some(cookie) = Cookie::parse(url, set_cookie) else {
+            return;
+        };
+        
+        let mut cookies = self.lock();
+        
+        cookies.retain(|current| ! (current.name == cookie.name && current.domain == cookie.domain && current.path == cookie.path));
+        
+        // an expiration date in the past is how servers remove cookies
+        if ! cookie.expired(now()) {
+            cookies.push(cookie);
+        }
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    fn lock(&self) -> MutexGuard<'_, Vec<Cookie>> {
+        self.cookies.lock().unwrap_or_else(|error| error.into_inner())
+    }
+    
+}
+
+impl Cookie {
+    
+    // https://www.rfc-editor.org/rfc/rfc6265#section-5.2
+    fn parse(url: &Url, set_cookie: &str) -> Option<Self> {
+        let mut parts = set_cookie.split(';');
+        
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        
+        if name.is_empty() {
+            return None;
+        }
+        
+        let host = url.connect_host();
+        
+        let mut cookie = Self {
+            domain: host.to_owned(),
+            include_subdomains: false,
+            path: default_path(url.path()),
+            secure: false,
+            http_only: false,
+            expires: 0,
+            name: name.to_owned(),
+            value: value.trim().to_owned(),
+        };
+        
+        let mut max_age = None;
+        
+        for attribute in parts {
+            
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            
+            match key.trim().to_ascii_lowercase().as_str() {
+                
+                "domain" if ! value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    // only the host itself or one of its parents can be targeted
+                    if host != domain && ! host.ends_with(&format!(".{}", domain)) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.include_subdomains = true;
+                },
+                
+                "path" if value.starts_with('/') => cookie.path = value.to_owned(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                
+                "expires" => {
+                    if let Some(expires) = parse_date(value) {
+                        // dates before the epoch are an expiration all the same
+                        cookie.expires = expires.max(1);
+                    }
+                },
+                
+                _ => (),
+                
+            }
+            
+        }
+        
+        // "Max-Age" takes precedence over "Expires"
+        if let Some(max_age) = max_age {
+            cookie.expires = match u64::try_from(max_age) {
+                Ok(seconds) if seconds > 0 => now().saturating_add(seconds),
+                _ => 1,
+            };
+        }
+        
+        Some(cookie)
+    }
+    
+    fn expired(&self, now: u64) -> bool {
+        self.expires != 0 && self.expires <= now
+    }
+    
+    fn domain_matches(&self, host: &str) -> bool {
+        host == self.domain || (self.include_subdomains && host.ends_with(&format!(".{}", self.domain)))
+    }
+    
+    // https://www.rfc-editor.org/rfc/rfc6265#section-5.1.4
+    fn path_matches(&self, path: &str) -> bool {
+        path == self.path || (path.starts_with(&self.path) && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')))
+    }
+    
+}
+
+fn default_path(path: &str) -> String {
+    match path.rfind('/') {
+        Some(0) | None => String::from("/"),
+        Some(index) => path[..index].to_owned(),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+// https://www.rfc-editor.org/rfc/rfc6265#section-5.1.1
+fn parse_date(value: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+    
+    for token in value.split(|current: char| ! current.is_ascii_alphanumeric() && current != ':').filter(|token| ! token.is_empty()) {
+        
+        if time.is_none() {
+            let fields: Vec<u64> = token.split(':').map_while(|field| field.parse().ok()).collect();
+            if let [hours, minutes, seconds] = fields[..] {
+                time = Some((hours, minutes, seconds));
+                continue;
+            }
+        }
+        
+        if day.is_none() && (1..=2).contains(&token.len()) && token.bytes().all(|byte| byte.is_ascii_digit()) {
+            day = token.parse::<u64>().ok();
+            continue;
+        }
+        
+        if month.is_none() {
+            let prefix = token.get(..3).map(str::to_ascii_lowercase);
+            if let Some(index) = MONTHS.iter().position(|name| Some(*name) == prefix.as_deref()) {
+                month = Some(index as u64 + 1);
+                continue;
+            }
+        }
+        
+        if year.is_none() && (2..=4).contains(&token.len()) && token.bytes().all(|byte| byte.is_ascii_digit()) {
+            year = token.parse::<u64>().ok().map(|year| match year {
+                0..=69 => year + 2000,
+                70..=99 => year + 1900,
+                year => year,
+            });
+        }
+        
+    }
+    
+    let (hours, minutes, seconds) = time?;
+    let (day, month, year) = (day?, month?, year?);
+    
+    if ! (1..=31).contains(&day) || year < 1601 || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    
+    // days from the civil calendar, as in http://howardhinnant.github.io/date_algorithms.html
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era;
+    
+    // dates before the epoch saturate to it, which callers treat as expired
+    Some((days * 86_400 + hours * 3_600 + minutes * 60 + seconds).saturating_sub(719_468 * 86_400))
+}
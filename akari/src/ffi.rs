@@ -95,12 +95,20 @@ extern "system" {
 pub type HINTERNET = *mut c_void;
 
 pub const WINHTTP_ACCESS_TYPE_DEFAULT_PROXY: c_ulong = 0; // DWORD
+pub const WINHTTP_ACCESS_TYPE_NAMED_PROXY: c_ulong = 3; // DWORD
 pub const WINHTTP_NO_PROXY_NAME: *const c_ushort = ptr::null(); // LPCWSTR -> WCHAR -> wchar_t
 pub const WINHTTP_NO_PROXY_BYPASS: *const c_ushort = ptr::null(); // LPCWSTR -> WCHAR -> wchar_t
 
 pub const WINHTTP_OPTION_ENABLE_HTTP_PROTOCOL: c_ulong = 133; // DWORD
 pub const WINHTTP_PROTOCOL_FLAG_HTTP2: c_ulong = 1; // DWORD
 
+pub const WINHTTP_OPTION_DISABLE_FEATURE: c_ulong = 63; // DWORD
+pub const WINHTTP_DISABLE_COOKIES: c_ulong = 0x0000_0001; // DWORD
+pub const WINHTTP_DISABLE_REDIRECTS: c_ulong = 0x0000_0002; // DWORD
+
+pub const WINHTTP_OPTION_PROXY_USERNAME: c_ulong = 0x1002; // DWORD
+pub const WINHTTP_OPTION_PROXY_PASSWORD: c_ulong = 0x1003; // DWORD
+
 pub const WINHTTP_NO_REFERER: *const c_ushort = ptr::null(); // // LPCWSTR -> WCHAR -> wchar_t
 pub const WINHTTP_DEFAULT_ACCEPT_TYPES: *mut *const c_ushort = ptr::null_mut(); // // LPCWSTR -> WCHAR -> wchar_t
 pub const WINHTTP_FLAG_SECURE: c_ulong = 0x0080_0000; // DWORD
@@ -108,7 +116,12 @@ pub const WINHTTP_FLAG_SECURE: c_ulong = 0x0080_0000; // DWORD
 pub const WINHTTP_NO_ADDITIONAL_HEADERS: *const c_ushort = ptr::null(); // // LPCWSTR -> WCHAR -> wchar_t
 pub const WINHTTP_NO_REQUEST_DATA: *mut c_void = ptr::null_mut(); // LPVOID
 
-pub const WINHTTP_QUERY_CONTENT_LENGTH: c_ulong = 5; // DWORD
+pub const ERROR_WINHTTP_TIMEOUT: i32 = 12002; // DWORD
+pub const ERROR_WINHTTP_CANNOT_CONNECT: i32 = 12029; // DWORD
+pub const ERROR_WINHTTP_CONNECTION_ERROR: i32 = 12030; // DWORD
+
+pub const WINHTTP_QUERY_STATUS_CODE: c_ulong = 19; // DWORD
+pub const WINHTTP_QUERY_RAW_HEADERS_CRLF: c_ulong = 22; // DWORD
 pub const WINHTTP_QUERY_FLAG_NUMBER: c_ulong = 0x2000_0000; // DWORD
 pub const WINHTTP_HEADER_NAME_BY_INDEX: *const c_ushort = ptr::null(); // // LPCWSTR -> WCHAR -> wchar_t
 pub const WINHTTP_NO_HEADER_INDEX: *mut c_ulong = ptr::null_mut(); // // LPCWSTR -> WCHAR -> wchar_t
@@ -0,0 +1,552 @@
+use std::{
+    io::{ self, Read },
+    mem,
+};
+
+// https://www.rfc-editor.org/rfc/rfc1950
+// https://www.rfc-editor.org/rfc/rfc1951
+// https://www.rfc-editor.org/rfc/rfc1952
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const INPUT_BUFFER_SIZE: usize = 16 * 1024;
+const MAX_CODE_BITS: usize = 15;
+const MAX_LITERAL_LENGTH_CODES: usize = 286;
+const MAX_DISTANCE_CODES: usize = 30;
+const END_OF_BLOCK: usize = 256;
+
+const LENGTH_BASE: [usize; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u32; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [usize; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [u32; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+const GZIP_FLAG_HEADER_CRC: u8 = 0x02;
+const GZIP_FLAG_EXTRA: u8 = 0x04;
+const GZIP_FLAG_NAME: u8 = 0x08;
+const GZIP_FLAG_COMMENT: u8 = 0x10;
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Gzip,
+    // zlib wrapper detected automatically, as some servers send raw streams
+    Deflate,
+}
+
+pub struct Inflater<R> {
+    input: BitReader<R>,
+    format: Format,
+    state: State,
+    last: bool,
+    window: Vec<u8>,
+    total: u64,
+    copy: (usize, usize),
+    checksum: Checksum,
+}
+
+enum State {
+    Start,
+    Block,
+    Stored(usize),
+    Compressed(Box<(Huffman, Huffman)>),
+    Trailer,
+    Done,
+}
+
+enum Checksum {
+    None,
+    Adler32(u32, u32),
+    Crc32(chikuwa::Crc32),
+}
+
+struct BitReader<R> {
+    inner: R,
+    buffer: Vec<u8>,
+    start: usize,
+    end: usize,
+    bits: u32,
+    count: u32,
+}
+
+struct Huffman {
+    counts: [usize; MAX_CODE_BITS + 1],
+    symbols: Vec<usize>,
+}
+
+impl<R: Read> Inflater<R> {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new(inner: R, format: Format) -> Self {
+        Self {
+            input: BitReader {
+                inner,
+                buffer: vec![0; INPUT_BUFFER_SIZE],
+                start: 0,
+                end: 0,
+                bits: 0,
+                count: 0,
+            },
+            format,
+            state: State::Start,
+            last: false,
+            window: vec![0; WINDOW_SIZE],
+            total: 0,
+            copy: (0, 0),
+            checksum: Checksum::None,
+        }
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    fn step(&mut self) -> io::Result<Option<u8>> {
+        let state = mem::replace(&mut self.state, State::Done);
+        
+        let (state, byte) = match state {
+            
+            State::Start => (self.start()?, None),
+            
+            State::Block => (self.block()?, None),
+            
+            State::Stored(0) => (State::Block, None),
+            
+            State::Stored(remaining) => (State::Stored(remaining - 1), Some(self.input.aligned_byte()?)),
+            
+            State::Compressed(tables) => {
+                
+                let symbol = tables.0.decode(&mut self.input)?;
+                
+                match symbol {
+                    
+                    0..=255 => (State::Compressed(tables), Some(u8::try_from(symbol).unwrap_or_default())),
+                    
+                    END_OF_BLOCK => (State::Block, None),
+                    
+                    _ => {
+                        
+                        let index = symbol - END_OF_BLOCK - 1;
+                        
+                        let length = LENGTH_BASE.get(index).ok_or_else(|| invalid("Invalid length symbol"))?
+                            + self.input.bits(LENGTH_EXTRA[index])? as usize;
+                        
+                        let index = tables.1.decode(&mut self.input)?;
+                        
+                        let distance = DISTANCE_BASE.get(index).ok_or_else(|| invalid("Invalid distance symbol"))?
+                            + self.input.bits(DISTANCE_EXTRA[index])? as usize;
+                        
+                        if distance as u64 > self.total {
+                            return Err(invalid("Distance too far back"));
+                        }
+                        
+                        self.copy = (distance, length);
+                        
+                        (State::Compressed(tables), None)
+                        
+                    },
+                    
+                }
+                
+            },
+            
+            State::Trailer => {
+                self.trailer()?;
+                (State::Done, None)
+            },
+            
+            State::Done => (State::Done, None),
+            
+        };
+        
+        self.state = state;
+        
+        Ok(byte)
+    }
+    
+    fn start(&mut self) -> io::Result<State> {
+        // bodyless responses, such as "304 Not Modified", might still declare an encoding
+        if self.input.is_exhausted()? {
+            return Ok(State::Done);
+        }
+        
+        match self.format {
+            
+            Format::Gzip => {
+                
+                let mut header = [0; 10];
+                
+                for byte in &mut header {
+                    *byte = self.input.aligned_byte()?;
+                }
+                
+                if header[..3] != [0x1F, 0x8B, 0x08] {
+                    return Err(invalid("Invalid gzip header"));
+                }
+                
+                let flags = header[3];
+                
+                if flags & GZIP_FLAG_EXTRA != 0 {
+                    let length = self.input.bits(16)?;
+                    for _ in 0..length {
+                        self.input.aligned_byte()?;
+                    }
+                }
+                
+                if flags & GZIP_FLAG_NAME != 0 {
+                    while self.input.aligned_byte()? != 0 {}
+                }
+                
+                if flags & GZIP_FLAG_COMMENT != 0 {
+                    while self.input.aligned_byte()? != 0 {}
+                }
+                
+                if flags & GZIP_FLAG_HEADER_CRC != 0 {
+                    self.input.bits(16)?;
+                }
+                
+                self.checksum = Checksum::Crc32(chikuwa::Crc32::new());
+                
+            },
+            
+            Format::Deflate => {
+                
+                let header = self.input.peek(16)?;
+                let method = header & 0xFF;
+                
+                // compression method 8, window size up to 32 KiB and valid check bits
+                if method & 0x0F == 8 && method >> 4 <= 7 && ((method << 8) | (header >> 8)) % 31 == 0 {
+                    
+                    if header & 0x2000 != 0 {
+                        return Err(invalid("Preset dictionaries are not supported"));
+                    }
+                    
+                    self.input.bits(16)?;
+                    self.checksum = Checksum::Adler32(1, 0);
+                    
+                }
+                
+            },
+            
+        }
+        
+        Ok(State::Block)
+    }
+    
+    fn block(&mut self) -> io::Result<State> {
+        if self.last {
+            return Ok(State::Trailer);
+        }
+        
+        self.last = self.input.bits(1)? == 1;
+        
+        match self.input.bits(2)? {
+            
+            0 => {
+                
+                self.input.align();
+                
+                let length = self.input.bits(16)?;
+                let complement = self.input.bits(16)?;
+                
+                if length != ! complement & 0xFFFF {
+                    return Err(invalid("Invalid stored block length"));
+                }
+                
+                Ok(State::Stored(length as usize))
+                
+            },
+            
+            1 => {
+                
+                let mut lengths = [0; 288 + MAX_DISTANCE_CODES];
+                
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..288].fill(8);
+                lengths[288..].fill(5);
+                
+                Ok(State::Compressed(Box::new((Huffman::new(&lengths[..288])?, Huffman::new(&lengths[288..])?))))
+                
+            },
+            
+            2 => {
+                
+                let literal_count = self.input.bits(5)? as usize + 257;
+                let distance_count = self.input.bits(5)? as usize + 1;
+                let code_count = self.input.bits(4)? as usize + 4;
+                
+                if literal_count > MAX_LITERAL_LENGTH_CODES || distance_count > MAX_DISTANCE_CODES {
+                    return Err(invalid("Invalid code counts"));
+                }
+                
+                let mut lengths = [0; CODE_LENGTH_ORDER.len()];
+                
+                for &index in &CODE_LENGTH_ORDER[..code_count] {
+                    lengths[index] = self.input.bits(3)? as u8;
+                }
+                
+                let code = Huffman::new(&lengths)?;
+                
+                let mut lengths = vec![0; literal_count + distance_count];
+                let mut index = 0;
+                
+                while index < lengths.len() {
+                    
+                    let (length, repeat) = match code.decode(&mut self.input)? {
+                        16 => (*lengths.get(index.wrapping_sub(1)).ok_or_else(|| invalid("Invalid code length repeat"))?, 3 + self.input.bits(2)? as usize),
+                        17 => (0, 3 + self.input.bits(3)? as usize),
+                        18 => (0, 11 + self.input.bits(7)? as usize),
+                        length => (length as u8, 1),
+                    };
+                    
+                    lengths.get_mut(index..index + repeat)
+                        .ok_or_else(|| invalid("Too many code lengths"))?
+                        .fill(length);
+                    
+                    index += repeat;
+                    
+                }
+                
+                if lengths[END_OF_BLOCK] == 0 {
+                    return Err(invalid("Missing end of block code"));
+                }
+                
+                let (literal, distance) = lengths.split_at(literal_count);
+                
+                Ok(State::Compressed(Box::new((Huffman::new(literal)?, Huffman::new(distance)?))))
+                
+            },
+            
+            _ => Err(invalid("Invalid block type")),
+            
+        }
+    }
+    
+    fn trailer(&mut self) -> io::Result<()> {
+        self.input.align();
+        
+        match self.checksum {
+            
+            Checksum::None => (),
+            
+            Checksum::Adler32(a, b) => {
+                
+                let mut expected = 0;
+                
+                for _ in 0..4 {
+                    expected = (expected << 8) | u32::from(self.input.aligned_byte()?);
+                }
+                
+                if expected != (b << 16) | a {
+                    return Err(invalid("Checksum mismatch"));
+                }
+                
+            },
+            
+            Checksum::Crc32(crc) => {
+                
+                let expected = self.input.bits(16)? | (self.input.bits(16)? << 16);
+                let size = self.input.bits(16)? | (self.input.bits(16)? << 16);
+                
+                #[allow(clippy::cast_possible_truncation)]
+                if expected != crc.finish() || size != self.total as u32 {
+                    return Err(invalid("Checksum mismatch"));
+                }
+                
+            },
+            
+        }
+        
+        Ok(())
+    }
+    
+    fn emit(&mut self, byte: u8) {
+        #[allow(clippy::cast_possible_truncation)]
+        let position = self.total as usize % WINDOW_SIZE;
+        
+        self.window[position] = byte;
+        self.total += 1;
+        
+        match &mut self.checksum {
+            Checksum::None => (),
+            Checksum::Adler32(a, b) => {
+                *a = (*a + u32::from(byte)) % 65521;
+                *b = (*b + *a) % 65521;
+            },
+            Checksum::Crc32(crc) => crc.update(&[byte]),
+        }
+    }
+    
+}
+
+impl<R: Read> Read for Inflater<R> {
+    
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        
+        while written < buf.len() {
+            
+            let byte = if self.copy.1 > 0 {
+                
+                let (distance, length) = self.copy;
+                self.copy = (distance, length - 1);
+                
+                #[allow(clippy::cast_possible_truncation)]
+                let position = (self.total as usize).wrapping_sub(distance) % WINDOW_SIZE;
+                
+                self.window[position]
+                
+            } else {
+                
+                if matches!(self.state, State::Done) {
+                    break;
+                }
+                
+                let Some(byte) = self.step()? else {
+                    continue;
+                };
+                
+                byte
+                
+            };
+            
+            self.emit(byte);
+            
+            buf[written] = byte;
+            written += 1;
+            
+        }
+        
+        Ok(written)
+    }
+    
+}
+
+impl<R: Read> BitReader<R> {
+    
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.start == self.end {
+            self.start = 0;
+            self.end = self.inner.read(&mut self.buffer)?;
+        }
+        
+        Ok(self.start < self.end)
+    }
+    
+    fn is_exhausted(&mut self) -> io::Result<bool> {
+        Ok(self.count == 0 && ! self.fill()?)
+    }
+    
+    fn byte(&mut self) -> io::Result<u8> {
+        if ! self.fill()? {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Compressed stream ended prematurely"));
+        }
+        
+        let byte = self.buffer[self.start];
+        self.start += 1;
+        
+        Ok(byte)
+    }
+    
+    fn aligned_byte(&mut self) -> io::Result<u8> {
+        if self.count >= 8 {
+            return Ok(self.bits(8)? as u8);
+        }
+        
+        self.byte()
+    }
+    
+    fn peek(&mut self, count: u32) -> io::Result<u32> {
+        while self.count < count {
+            self.bits |= u32::from(self.byte()?) << self.count;
+            self.count += 8;
+        }
+        
+        Ok(self.bits & ((1 << count) - 1))
+    }
+    
+    fn bits(&mut self, count: u32) -> io::Result<u32> {
+        let value = self.peek(count)?;
+        
+        self.bits >>= count;
+        self.count -= count;
+        
+        Ok(value)
+    }
+    
+    fn align(&mut self) {
+        let skip = self.count % 8;
+        
+        self.bits >>= skip;
+        self.count -= skip;
+    }
+    
+}
+
+impl Huffman {
+    
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let mut counts = [0; MAX_CODE_BITS + 1];
+        
+        for &length in lengths {
+            counts[usize::from(length)] += 1;
+        }
+        
+        // incomplete codes are tolerated, over-subscribed ones are not
+        
+        let mut left: usize = 1;
+        
+        for &count in &counts[1..] {
+            left = (left << 1).checked_sub(count).ok_or_else(|| invalid("Over-subscribed code"))?;
+        }
+        
+        let mut offsets = [0; MAX_CODE_BITS + 2];
+        
+        for length in 1..=MAX_CODE_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        
+        let mut symbols = vec![0; lengths.len()];
+        
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[usize::from(length)]] = symbol;
+                offsets[usize::from(length)] += 1;
+            }
+        }
+        
+        Ok(Self {
+            counts,
+            symbols,
+        })
+    }
+    
+    fn decode<R: Read>(&self, input: &mut BitReader<R>) -> io::Result<usize> {
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+        
+        for &count in &self.counts[1..] {
+            
+            code |= input.bits(1)? as usize;
+            
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+            
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+            
+        }
+        
+        Err(invalid("Invalid code"))
+    }
+    
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -1,28 +1,109 @@
 mod ffi;
 mod session;
 mod connection;
+mod pool;
+mod batch;
 mod payload;
+mod request;
+mod cache;
+mod cookies;
+mod inflate;
+mod proxy;
+mod socks;
+mod url;
+mod timeouts;
+mod redirect_policy;
+mod retry_policy;
+mod transport;
+mod winhttp;
+mod portable;
+mod mock;
 
-use std::io;
+use std::{
+    io,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
 
 use session::Session;
 use connection::Connection;
+use pool::Pool;
+use socks::Bridge;
+use proxy::ProxyScheme;
+use winhttp::WinHttp;
+use portable::Portable;
 
 pub use payload::Payload;
+pub use request::RequestBuilder;
+pub use pool::PoolStats;
+pub use batch::Batch;
+pub use cache::{ Cache, Validators };
+pub use cookies::CookieJar;
+pub use proxy::Proxy;
+pub use url::Url;
+pub use timeouts::Timeouts;
+pub use redirect_policy::RedirectPolicy;
+pub use retry_policy::RetryPolicy;
+pub use transport::{ Backend, Transport, Request, Response };
+pub use mock::{ MockTransport, MockResponse, RecordedRequest };
 
 pub struct Client {
-    session: Session,
+    transport: Box<dyn Transport>,
+    redirect_policy: RedirectPolicy,
+    retry_policy: RetryPolicy,
+    cookie_jar: Option<CookieJar>,
 }
 
+#[derive(Clone)]
+pub struct ClientBuilder {
+    timeouts: Timeouts,
+    idle_timeout: Option<Duration>,
+    redirect_policy: RedirectPolicy,
+    retry_policy: RetryPolicy,
+    proxy: Option<Proxy>,
+    cookie_jar: Option<CookieJar>,
+    user_agent: String,
+    backend: Backend,
+    transport: Option<TransportFactory>,
+}
+
+// every client built from the same configuration needs its own transport
+type TransportFactory = Arc<dyn Fn() -> Box<dyn Transport> + Send + Sync>;
+
 impl Client {
     
     // -------------------- constructors --------------------
     
     
     pub fn new() -> io::Result<Self> {
-        Ok(Self {
-            session: Session::new()?,
-        })
+        Self::builder().build()
+    }
+    
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder {
+            timeouts: Timeouts::default(),
+            idle_timeout: None,
+            redirect_policy: RedirectPolicy::default(),
+            retry_policy: RetryPolicy::default(),
+            proxy: None,
+            cookie_jar: None,
+            user_agent: String::from(env!("CARGO_PKG_NAME")),
+            backend: Backend::default(),
+            transport: None,
+        }
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn pool_stats(&self) -> PoolStats {
+        self.transport.pool_stats()
+    }
+    
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.cookie_jar.as_ref()
     }
     
     
@@ -30,43 +111,172 @@ impl Client {
     
     
     pub fn get(&mut self, url: &str) -> io::Result<Payload> {
-        let (host, port, path, secure) = Self::extract_params(url)?;
-        
-        let connection = Connection::new(&self.session, host, port)?;
-        
-        Payload::new(&connection, path, secure)
+        self.request(url).send()
+    }
+    
+    pub fn request<'c>(&'c mut self, url: &'c str) -> RequestBuilder<'c> {
+        RequestBuilder::new(self, url)
     }
     
     
     // -------------------- helpers --------------------
     
     
-    fn extract_params(url: &str) -> io::Result<(&str, u16, &str, bool)> {
+    fn execute(&mut self, url: &str, headers: &str, timeouts: Option<&Timeouts>, ranged: bool) -> io::Result<Payload> {
+        let mut current = Url::parse(url)?;
+        let mut hops = 0;
+        
+        if ! matches!(current.scheme(), "http" | "https") {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Unsupported scheme"));
+        }
+        
+        loop {
+            
+            let payload = self.attempt(&current, headers, timeouts)?;
+            
+            // captured on every hop, as redirects are commonly used to set session cookies
+            if let Some(cookie_jar) = &self.cookie_jar {
+                for set_cookie in payload.set_cookies() {
+                    cookie_jar.capture(&current, set_cookie);
+                }
+            }
+            
+            let status = payload.status();
+            
+            // redirects are left to the caller when none are allowed
+            if ! matches!(status, 301 | 302 | 303 | 307 | 308) || self.redirect_policy.max_hops == 0 {
+                
+                // an unsatisfiable range means there is nothing left to send
+                if status >= 400 && ! (ranged && status == 416) {
+                    return Err(io::Error::other(format!("Unexpected status code: {}", status)));
+                }
+                
+                return Ok(payload);
+                
+            }
+            
+            let location = payload.location()
+                .ok_or(io::Error::new(io::ErrorKind::InvalidData, "Redirect location not provided"))?;
+            
+            hops += 1;
+            current = self.redirect_policy.follow(&current, location, hops)?;
+            
+        }
+    }
+    
+    fn attempt(&mut self, url: &Url, headers: &str, timeouts: Option<&Timeouts>) -> io::Result<Payload> {
+        let headers = match self.cookie_jar.as_ref().and_then(|cookie_jar| cookie_jar.header(url)) {
+            Some(cookie) => format!("{}{}", headers, cookie),
+            None => headers.to_owned(),
+        };
         
-        fn extract<'a>(url: &'a str, scheme: &str, default_port: u16, secure: bool) -> Option<(&'a str, u16, &'a str, bool)> {
-            let base = url.strip_prefix(scheme)?;
+        let mut attempt = 0;
+        
+        loop {
             
-            let (host_plus_port, path) = match base.find('/') {
-                Some(index) => (&base[..index], &base[index..]),
-                None => (base, "/"),
+            let request = Request {
+                url,
+                headers: &headers,
+                timeouts,
             };
             
-            let (host, port) = match host_plus_port.split_once(':') {
-                Some((host, port)) => (host, port.parse().ok()?),
-                None => (host_plus_port, default_port),
+            let result = self.transport.send(&request).and_then(Payload::new);
+            
+            // only "GET" requests are issued, so every transient failure can be retried
+            let retry_after = match &result {
+                Ok(payload) if payload.status() == 429 || payload.status() >= 500 => payload.retry_after(),
+                Ok(_) => return result,
+                Err(error) if is_transient(error) => None,
+                Err(_) => return result,
             };
             
-            if host.is_empty() {
-                return None;
+            if attempt >= self.retry_policy.max_retries {
+                return result;
             }
             
-            Some((host, port, path, secure))
+            // release the handle before sleeping
+            drop(result);
+            
+            thread::sleep(self.retry_policy.delay(attempt, retry_after));
+            attempt += 1;
+            
         }
+    }
+    
+    
+}
+
+impl ClientBuilder {
+    
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+    
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+    
+    pub fn with_redirect_policy(mut self, redirect_policy: RedirectPolicy) -> Self {
+        self.redirect_policy = redirect_policy;
+        self
+    }
+    
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+    
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+    
+    pub fn with_cookie_jar(mut self, cookie_jar: CookieJar) -> Self {
+        self.cookie_jar = Some(cookie_jar);
+        self
+    }
+    
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_owned();
+        self
+    }
+    
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+    
+    // replaces the backend, making timeouts, idle timeout, proxy and user agent the responsibility of the transport
+    pub fn with_transport<T: Transport + Clone + Send + Sync + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(move || Box::new(transport.clone())));
+        self
+    }
+    
+    pub fn build(self) -> io::Result<Client> {
+        let transport: Box<dyn Transport> = match (&self.transport, self.backend) {
+            (Some(factory), _) => factory(),
+            (None, Backend::WinHttp) => Box::new(WinHttp::new(&self.user_agent, &self.timeouts, self.idle_timeout, self.proxy)?),
+            (None, Backend::Portable) => Box::new(Portable::new(&self.user_agent, &self.timeouts, self.idle_timeout, self.proxy)),
+        };
         
-        extract(url, "https://", 443, true)
-            .or_else(|| extract(url, "http://", 80, false))
-            .ok_or(io::Error::new(io::ErrorKind::InvalidInput, "Invalid URL"))
-        
-    }    
+        Ok(Client {
+            transport,
+            redirect_policy: self.redirect_policy,
+            retry_policy: self.retry_policy,
+            cookie_jar: self.cookie_jar,
+        })
+    }
     
 }
+
+// failures to connect or to get an answer in time, leaving out those that would happen again, such as TLS or name resolution errors
+fn is_transient(error: &io::Error) -> bool {
+    matches!(error.kind(),
+        io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::NotConnected
+        | io::ErrorKind::TimedOut)
+}
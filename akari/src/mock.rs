@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    io::{ self, Cursor },
+    sync::{ Arc, Mutex, MutexGuard },
+};
+
+use super::{
+    Url,
+    transport::{ Transport, Request, Response },
+};
+
+// cloning shares the responses and the recorded requests, so that they can be inspected after handing it to a client
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct RecordedRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct State {
+    responses: HashMap<String, Vec<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockTransport {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // registering several responses for the same url serves them in order, repeating the last one
+    pub fn with_response(self, url: &str, response: MockResponse) -> Self {
+        let key = Url::parse(url).map_or_else(|_| url.to_owned(), |url| url.to_string());
+        self.lock().responses.entry(key).or_default().push(response);
+        self
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+    
+}
+
+impl Transport for MockTransport {
+    
+    fn send(&mut self, request: &Request) -> io::Result<Response> {
+        let url = request.url.to_string();
+        
+        let mut state = self.lock();
+        
+        state.requests.push(RecordedRequest {
+            url: url.clone(),
+            headers: request.headers.split("\r\n")
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+                .collect(),
+        });
+        
+        let response = match state.responses.get_mut(&url) {
+            Some(responses) if responses.len() > 1 => responses.remove(0),
+            Some(responses) => responses[0].clone(),
+            None => MockResponse::new(404, ""),
+        };
+        
+        Ok(Response {
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(Cursor::new(response.body)),
+        })
+    }
+    
+}
+
+impl MockResponse {
+    
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+    
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+    
+}
@@ -1,14 +1,30 @@
 use std::{
     io::{ self, Read },
-    mem,
-    os::raw::*,
-    ptr,
+    time::Duration,
 };
 
-use super::{ ffi, Connection };
+use super::{
+    Validators,
+    inflate::{ Inflater, Format },
+    transport::Response,
+};
 
 pub struct Payload {
-    handle: ffi::HINTERNET,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Body,
+    offset: u64,
+    total: Option<u64>,
+    transferred: u64,
+    size_limit: Option<u64>,
+    progress: Option<Progress>,
+}
+
+pub(crate) type Progress = Box<dyn FnMut(u64, Option<u64>)>;
+
+enum Body {
+    Identity(Box<dyn Read>),
+    Encoded(Inflater<Box<dyn Read>>),
 }
 
 impl Payload {
@@ -16,139 +32,167 @@ impl Payload {
     // -------------------- constructors --------------------
     
     
-    pub fn new(connection: &Connection, path: &str, secure: bool) -> io::Result<Self> {
-        // -------------------- handle --------------------
+    pub(crate) fn new(response: Response) -> io::Result<Self> {
+        // -------------------- encoding --------------------
         
-        let handle = unsafe {
-            
-            let flags = if secure {
-                ffi::WINHTTP_FLAG_SECURE
-            } else {
-                0
-            };
-            
-            let handle = ffi::WinHttpOpenRequest(
-                connection.handle,
-                chikuwa::WinString::from("GET").as_ptr(),
-                chikuwa::WinString::from(path).as_ptr(),
-                ptr::null(),
-                ffi::WINHTTP_NO_REFERER,
-                ffi::WINHTTP_DEFAULT_ACCEPT_TYPES,
-                flags,
-            );
-            
-            if handle.is_null() {
-                return Err(io::Error::last_os_error());
-            }
-            
-            handle
-            
+        let encoding = find(&response.headers, "Content-Encoding")
+            .map(str::to_ascii_lowercase);
+        
+        let format = match encoding.as_deref() {
+            None | Some("" | "identity") => None,
+            Some("gzip" | "x-gzip") => Some(Format::Gzip),
+            Some("deflate") => Some(Format::Deflate),
+            Some(_) => return Err(io::Error::new(io::ErrorKind::Unsupported, "Unsupported content encoding")),
+        };
+        
+        let body = match format {
+            Some(format) => Body::Encoded(Inflater::new(response.body, format)),
+            None => Body::Identity(response.body),
         };
         
-        // -------------------- send --------------------
+        let mut payload = Self {
+            status: response.status,
+            headers: response.headers,
+            body,
+            offset: 0,
+            total: None,
+            transferred: 0,
+            size_limit: None,
+            progress: None,
+        };
         
-        unsafe {
-            
-            let result = ffi::WinHttpSendRequest(
-                handle,
-                ffi::WINHTTP_NO_ADDITIONAL_HEADERS,
-                0,
-                ffi::WINHTTP_NO_REQUEST_DATA,
-                0,
-                0,
-                0,
-            );
-            
-            if result == 0 {
-                ffi::WinHttpCloseHandle(handle);
-                return Err(io::Error::last_os_error());
-            }
-            
-        }
+        // -------------------- range --------------------
         
-        // -------------------- receive --------------------
+        // "bytes first-last/complete", with an asterisk if the complete length is unknown
+        let range = payload.header("Content-Range")
+            .filter(|_| payload.status == 206)
+            .and_then(|range| {
+                let (first, complete) = range.strip_prefix("bytes ")?.split_once('/')?;
+                let first = first.split_once('-')?.0.trim().parse::<u64>().ok()?;
+                Some((first, complete.trim().parse::<u64>().ok()))
+            });
         
-        unsafe {
-            
-            let result = ffi::WinHttpReceiveResponse(
-                handle,
-                ptr::null_mut(),
-            );
-            
-            if result == 0 {
-                ffi::WinHttpCloseHandle(handle);
-                return Err(io::Error::last_os_error());
-            }
-            
+        if let Some((first, complete)) = range {
+            payload.offset = first;
+            payload.total = complete;
+            payload.transferred = first;
+        } else if format.is_none() {
+            // the decoded length of an encoded body is only known once it has been read
+            payload.total = Some(payload.content_length() as u64).filter(|&length| length > 0);
         }
         
-        Ok(Self { handle })
+        Ok(payload)
     }
     
     
     // -------------------- accessors --------------------
     
     
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+    
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find(&self.headers, name)
+    }
+    
     pub fn content_length(&self) -> usize {
-        unsafe {
-            
-            let mut content_length: c_ulong = 0;
-            
-            #[allow(clippy::cast_possible_truncation)]
-            let mut bytes = mem::size_of::<c_ulong>() as c_ulong;
-            
-            ffi::WinHttpQueryHeaders(
-                self.handle,
-                ffi::WINHTTP_QUERY_CONTENT_LENGTH | ffi::WINHTTP_QUERY_FLAG_NUMBER,
-                ffi::WINHTTP_HEADER_NAME_BY_INDEX,
-                ptr::from_mut(&mut content_length).cast::<c_void>(),
-                &mut bytes,
-                ffi::WINHTTP_NO_HEADER_INDEX,
-            );
-            
-            content_length as usize
-            
+        self.header("Content-Length")
+            .and_then(|length| length.parse().ok())
+            .unwrap_or(0)
+    }
+    
+    pub fn total_length(&self) -> Option<u64> {
+        self.total
+    }
+    
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    
+    pub fn unchanged(&self) -> bool {
+        self.status == 304
+    }
+    
+    pub fn validators(&self) -> Validators {
+        Validators {
+            etag: self.header("ETag").map(str::to_owned),
+            last_modified: self.header("Last-Modified").map(str::to_owned),
         }
     }
     
-}
-
-impl Read for Payload {
     
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        unsafe {
-            
-            let mut amount_read: c_ulong = 0;
-            
-            #[allow(clippy::cast_possible_truncation)]
-            let bytes = buf.len() as c_ulong;
-            
-            let result = ffi::WinHttpReadData(
-                self.handle,
-                ptr::from_mut(buf).cast::<c_void>(),
-                bytes,
-                &mut amount_read,
-            );
-            
-            if result == 0 {
-                return Err(io::Error::last_os_error());
+    // -------------------- mutators --------------------
+    
+    
+    pub(crate) fn with_size_limit(mut self, size_limit: Option<u64>) -> io::Result<Self> {
+        if let (Some(limit), Some(total)) = (size_limit, self.total) {
+            if total > limit {
+                return Err(size_limit_exceeded());
             }
-            
-            Ok(amount_read as usize)
-            
         }
+        self.size_limit = size_limit;
+        Ok(self)
+    }
+    
+    pub(crate) fn with_progress(mut self, progress: Option<Progress>) -> Self {
+        self.progress = progress;
+        self
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    pub(crate) fn location(&self) -> Option<&str> {
+        self.header("Location")
+    }
+    
+    pub(crate) fn set_cookies(&self) -> impl Iterator<Item = &str> {
+        self.headers.iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("Set-Cookie"))
+            .map(|(_, value)| value.as_str())
+    }
+    
+    pub(crate) fn retry_after(&self) -> Option<Duration> {
+        // the http-date form is not supported and will result in the default backoff being used
+        self.header("Retry-After")
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_secs)
     }
     
 }
 
-impl Drop for Payload {
+impl Read for Payload {
     
-    fn drop(&mut self) {
-        unsafe {
-            
-            ffi::WinHttpCloseHandle(self.handle);
-            
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes = match &mut self.body {
+            Body::Identity(stream) => stream.read(buf)?,
+            Body::Encoded(inflater) => inflater.read(buf)?,
+        };
+        
+        self.transferred += bytes as u64;
+        
+        // checked on the decoded bytes, so that compressed bodies cannot get around it
+        if self.size_limit.is_some_and(|limit| self.transferred > limit) {
+            return Err(size_limit_exceeded());
         }
+        
+        if let Some(progress) = self.progress.as_mut().filter(|_| bytes > 0) {
+            progress(self.transferred, self.total);
+        }
+        
+        Ok(bytes)
     }
     
 }
+
+fn find<'h>(headers: &'h [(String, String)], name: &str) -> Option<&'h str> {
+    headers.iter()
+        .find(|(current, _)| current.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn size_limit_exceeded() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Response body exceeds the size limit")
+}
@@ -0,0 +1,98 @@
+use std::{
+    collections::HashMap,
+    io,
+    time::{ Duration, Instant },
+};
+
+use super::{ Connection, Session };
+
+pub(crate) const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+// caches connection handles, which own no socket: connections themselves are kept alive and reused by WinHTTP at session level
+pub struct Pool {
+    idle_timeout: Duration,
+    connections: HashMap<(String, u16), Entry>,
+    stats: PoolStats,
+}
+
+struct Entry {
+    connection: Connection,
+    last_used: Instant,
+}
+
+// connection handles are counted by the WinHTTP backend, which reuses network connections on its own, while these are counted by the portable one
+#[derive(Clone, Copy, Default)]
+pub struct PoolStats {
+    pub handles_opened: u64,
+    pub handles_reused: u64,
+    pub handles_evicted: u64,
+    pub connections_opened: u64,
+    pub connections_reused: u64,
+    pub connections_evicted: u64,
+}
+
+impl Default for Pool {
+    
+    fn default() -> Self {
+        Self::new(DEFAULT_IDLE_TIMEOUT)
+    }
+    
+}
+
+impl Pool {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            idle_timeout,
+            connections: HashMap::new(),
+            stats: PoolStats::default(),
+        }
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+    
+    
+    // -------------------- mutators --------------------
+    
+    
+    pub fn acquire(&mut self, session: &Session, host: &str, port: u16) -> io::Result<&Connection> {
+        self.evict_idle();
+        
+        let key = (host.to_owned(), port);
+        
+        if let Some(entry) = self.connections.get_mut(&key) {
+            entry.last_used = Instant::now();
+            self.stats.handles_reused += 1;
+        } else {
+            let connection = Connection::new(session, host, port)?;
+            self.connections.insert(key.clone(), Entry { connection, last_used: Instant::now() });
+            self.stats.handles_opened += 1;
+        }
+        
+        Ok(&self.connections[&key].connection)
+    }
+    
+    pub fn discard(&mut self, host: &str, port: u16) {
+        if self.connections.remove(&(host.to_owned(), port)).is_some() {
+            self.stats.handles_evicted += 1;
+        }
+    }
+    
+    fn evict_idle(&mut self) {
+        let before = self.connections.len();
+        
+        self.connections.retain(|_, entry| entry.last_used.elapsed() < self.idle_timeout);
+        
+        self.stats.handles_evicted += (before - self.connections.len()) as u64;
+    }
+    
+}
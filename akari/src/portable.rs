@@ -0,0 +1,433 @@
+use std::{
+    collections::HashMap,
+    io::{ self, BufRead, BufReader, Read, Write },
+    net::{ TcpStream, ToSocketAddrs },
+    sync::{ Arc, Mutex, MutexGuard },
+    time::{ Duration, Instant },
+};
+
+use super::{
+    socks, PoolStats, Proxy, ProxyScheme, Timeouts, Url,
+    pool::DEFAULT_IDLE_TIMEOUT,
+    proxy::Endpoint,
+    transport::{ Transport, Request, Response },
+};
+
+const RESPONSE_HEAD_SIZE_LIMIT: usize = 64 * 1024;
+
+// HTTP/1.1 over plain TCP connections, without TLS
+pub struct Portable {
+    user_agent: String,
+    timeouts: Timeouts,
+    proxy: Option<Proxy>,
+    pool: Arc<Mutex<Idle>>,
+}
+
+// connections whose last response was read completely, shared with the bodies giving them back
+struct Idle {
+    timeout: Duration,
+    connections: HashMap<(String, u16), Vec<Pooled>>,
+    stats: PoolStats,
+}
+
+struct Pooled {
+    stream: BufReader<TcpStream>,
+    last_used: Instant,
+}
+
+struct Head {
+    status: u16,
+    headers: Vec<(String, String)>,
+    // whether the connection can be kept open after the response, which defaults to no before HTTP/1.1
+    persistent: bool,
+}
+
+struct Body {
+    stream: Option<BufReader<TcpStream>>,
+    framing: Framing,
+    reusable: bool,
+    key: (String, u16),
+    pool: Arc<Mutex<Idle>>,
+}
+
+enum Framing {
+    Length(u64),
+    // size left in the current chunk, none before the next chunk header
+    Chunked(Option<u64>),
+    Close,
+}
+
+impl Portable {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new(user_agent: &str, timeouts: &Timeouts, idle_timeout: Option<Duration>, proxy: Option<Proxy>) -> Self {
+        Self {
+            user_agent: user_agent.to_owned(),
+            timeouts: *timeouts,
+            proxy,
+            pool: Arc::new(Mutex::new(Idle {
+                timeout: idle_timeout.unwrap_or(DEFAULT_IDLE_TIMEOUT),
+                connections: HashMap::new(),
+                stats: PoolStats::default(),
+            })),
+        }
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    fn connect(&self, url: &Url, endpoint: Option<&Endpoint>, timeouts: &Timeouts) -> io::Result<TcpStream> {
+        let host = url.connect_host();
+        let port = url.port().unwrap_or_default();
+        
+        let stream = match endpoint {
+            Some(endpoint) if endpoint.scheme() == ProxyScheme::Socks5 => socks::connect(endpoint, host, port, None)?,
+            Some(endpoint) => open(endpoint.host().trim_start_matches('[').trim_end_matches(']'), endpoint.port(), timeouts.connect)?,
+            None => open(host, port, timeouts.connect)?,
+        };
+        
+        lock(&self.pool).stats.connections_opened += 1;
+        
+        Ok(stream)
+    }
+    
+    fn response(&self, stream: BufReader<TcpStream>, head: Head, key: (String, u16)) -> Response {
+        let Head { status, headers, persistent } = head;
+        
+        let header = |name: &str| headers.iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str());
+        
+        let framing = if matches!(status, 204 | 304) {
+            Framing::Length(0)
+        } else if header("Transfer-Encoding").is_some_and(|encoding| encoding.to_ascii_lowercase().trim_end().ends_with("chunked")) {
+            Framing::Chunked(None)
+        } else if let Some(length) = header("Content-Length").and_then(|length| length.trim().parse().ok()) {
+            Framing::Length(length)
+        } else {
+            Framing::Close
+        };
+        
+        let reusable = persistent && ! matches!(framing, Framing::Close);
+        
+        let mut body = Body {
+            stream: Some(stream),
+            framing,
+            reusable,
+            key,
+            pool: Arc::clone(&self.pool),
+        };
+        
+        // responses without a body give their connection back right away
+        if matches!(body.framing, Framing::Length(0)) {
+            body.finish();
+        }
+        
+        Response {
+            status,
+            headers,
+            body: Box::new(body),
+        }
+    }
+    
+}
+
+impl Transport for Portable {
+    
+    fn send(&mut self, request: &Request) -> io::Result<Response> {
+        if request.url.is_secure() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "HTTPS is not supported by the portable backend"));
+        }
+        
+        let timeouts = request.timeouts.unwrap_or(&self.timeouts);
+        let host = request.url.connect_host();
+        let port = request.url.port().unwrap_or_default();
+        let key = (host.to_ascii_lowercase(), port);
+        
+        let endpoint = self.proxy.as_ref()
+            .filter(|proxy| ! proxy.bypasses(host))
+            .and_then(|proxy| proxy.endpoint(request.url.scheme()));
+        
+        let head = head(request, endpoint, &self.user_agent);
+        
+        // a pooled connection might have been closed by the server in the meantime, so a failure is followed by a single attempt on a new one
+        let pooled = lock(&self.pool).take(&key);
+        
+        if let Some(stream) = pooled {
+            match exchange(stream, &head, timeouts) {
+                Ok((stream, head)) => return Ok(self.response(stream, head, key)),
+                Err(_) => lock(&self.pool).stats.connections_evicted += 1,
+            }
+        }
+        
+        let stream = BufReader::new(self.connect(request.url, endpoint, timeouts)?);
+        
+        let (stream, head) = exchange(stream, &head, timeouts)?;
+        
+        Ok(self.response(stream, head, key))
+    }
+    
+    fn pool_stats(&self) -> PoolStats {
+        lock(&self.pool).stats
+    }
+    
+}
+
+impl Idle {
+    
+    fn take(&mut self, key: &(String, u16)) -> Option<BufReader<TcpStream>> {
+        let timeout = self.timeout;
+        let mut evicted = 0;
+        
+        self.connections.retain(|_, connections| {
+            let before = connections.len();
+            connections.retain(|pooled| pooled.last_used.elapsed() < timeout);
+            evicted += (before - connections.len()) as u64;
+            ! connections.is_empty()
+        });
+        
+        self.stats.connections_evicted += evicted;
+        
+        let pooled = self.connections.get_mut(key)?.pop()?;
+        
+        self.stats.connections_reused += 1;
+        
+        Some(pooled.stream)
+    }
+    
+}
+
+impl Body {
+    
+    // gives the connection back to the pool once the response has been read completely
+    fn finish(&mut self) {
+        if let Some(stream) = self.stream.take().filter(|_| self.reusable) {
+            lock(&self.pool).connections
+                .entry(self.key.clone())
+                .or_default()
+                .push(Pooled { stream, last_used: Instant::now() });
+        }
+    }
+    
+}
+
+impl Read for Body {
+    
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(stream) = self.stream.as_mut() else {
+            return Ok(0);
+        };
+        
+        match self.framing {
+            
+            Framing::Length(remaining) => {
+                
+                let limit = usize::try_from(remaining).unwrap_or(usize::MAX).min(buf.len());
+                let bytes = stream.read(&mut buf[..limit]).map_err(timed_out)?;
+                
+                if bytes == 0 && limit > 0 {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed before the end of the response"));
+                }
+                
+                self.framing = Framing::Length(remaining - bytes as u64);
+                
+                if remaining == bytes as u64 {
+                    self.finish();
+                }
+                
+                Ok(bytes)
+                
+            },
+            
+            Framing::Chunked(None) => {
+                
+                let line = read_line(stream)?;
+                
+                // chunk extensions are ignored
+                let size = line.split(';').next().unwrap_or_default().trim();
+                let size = u64::from_str_radix(size, 16)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid chunk size"))?;
+                
+                if size > 0 {
+                    self.framing = Framing::Chunked(Some(size));
+                    return self.read(buf);
+                }
+                
+                // trailers are ignored
+                while ! read_line(stream)?.is_empty() {}
+                
+                self.framing = Framing::Length(0);
+                self.finish();
+                
+                Ok(0)
+                
+            },
+            
+            Framing::Chunked(Some(remaining)) => {
+                
+                let limit = usize::try_from(remaining).unwrap_or(usize::MAX).min(buf.len());
+                let bytes = stream.read(&mut buf[..limit]).map_err(timed_out)?;
+                
+                if bytes == 0 && limit > 0 {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed before the end of the response"));
+                }
+                
+                if remaining == bytes as u64 {
+                    // every chunk ends with a line break of its own
+                    read_line(stream)?;
+                    self.framing = Framing::Chunked(None);
+                } else {
+                    self.framing = Framing::Chunked(Some(remaining - bytes as u64));
+                }
+                
+                Ok(bytes)
+                
+            },
+            
+            Framing::Close => stream.read(buf).map_err(timed_out),
+            
+        }
+    }
+    
+}
+
+fn lock(pool: &Mutex<Idle>) -> MutexGuard<'_, Idle> {
+    pool.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+fn open(host: &str, port: u16, timeout: Duration) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "Host name resolved to no address");
+    
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = timed_out(error),
+        }
+    }
+    
+    Err(last_error)
+}
+
+fn head(request: &Request, endpoint: Option<&Endpoint>, user_agent: &str) -> String {
+    let url = request.url;
+    let port = url.port().unwrap_or_default();
+    
+    let host = match url.port() {
+        Some(80) | None => url.host().to_owned(),
+        Some(port) => format!("{}:{}", url.host(), port),
+    };
+    
+    // http proxies expect the absolute form of the request target
+    let target = match endpoint {
+        Some(endpoint) if endpoint.scheme() == ProxyScheme::Http => format!("{}://{}:{}{}", url.scheme(), url.host(), port, url.request_target()),
+        _ => url.request_target(),
+    };
+    
+    let mut head = format!("GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\n", target, host, user_agent);
+    
+    if let Some((username, password)) = endpoint.filter(|endpoint| endpoint.scheme() == ProxyScheme::Http).and_then(Endpoint::credentials) {
+        head.push_str(&format!("Proxy-Authorization: Basic {}\r\n", socks::encode_base64(format!("{}:{}", username, password).as_bytes())));
+    }
+    
+    head.push_str(request.headers);
+    head.push_str("\r\n");
+    
+    head
+}
+
+// sends the request and reads the status and headers of its response, interim responses being skipped
+fn exchange(mut stream: BufReader<TcpStream>, head: &str, timeouts: &Timeouts) -> io::Result<(BufReader<TcpStream>, Head)> {
+    stream.get_ref().set_write_timeout(Some(timeouts.send).filter(|timeout| ! timeout.is_zero()))?;
+    stream.get_ref().set_read_timeout(Some(timeouts.receive).filter(|timeout| ! timeout.is_zero()))?;
+    
+    stream.get_mut().write_all(head.as_bytes()).map_err(timed_out)?;
+    
+    loop {
+        
+        let line = read_line(&mut stream)?;
+        
+        let mut parts = line.splitn(3, ' ');
+        
+        let (version, status) = match (parts.next(), parts.next()) {
+            (Some(version), Some(status)) if version.starts_with("HTTP/1.") => (version, status.parse::<u16>().ok()),
+            _ => (line.as_str(), None),
+        };
+        
+        let status = status.ok_or(io::Error::new(io::ErrorKind::InvalidData, "Invalid status line"))?;
+        
+        let mut headers = Vec::new();
+        let mut size = line.len();
+        
+        loop {
+            
+            let line = read_line(&mut stream)?;
+            
+            if line.is_empty() {
+                break;
+            }
+            
+            size += line.len();
+            
+            if size > RESPONSE_HEAD_SIZE_LIMIT {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Response head exceeds the size limit"));
+            }
+            
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_owned(), value.trim().to_owned()));
+            }
+            
+        }
+        
+        if ! (100..200).contains(&status) {
+            
+            let connection = headers.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("Connection"))
+                .map(|(_, value)| value.to_ascii_lowercase());
+            
+            let persistent = match connection.as_deref() {
+                Some("close") => false,
+                Some("keep-alive") => true,
+                _ => version != "HTTP/1.0",
+            };
+            
+            return Ok((stream, Head { status, headers, persistent }));
+            
+        }
+        
+    }
+}
+
+// without its line break, a connection closed before any being reported as reset
+fn read_line(stream: &mut BufReader<TcpStream>) -> io::Result<String> {
+    let mut line = Vec::new();
+    
+    let bytes = stream.by_ref()
+        .take(RESPONSE_HEAD_SIZE_LIMIT as u64)
+        .read_until(b'\n', &mut line)
+        .map_err(timed_out)?;
+    
+    if bytes == 0 {
+        return Err(io::Error::new(io::ErrorKind::ConnectionReset, "Connection closed by the server"));
+    }
+    
+    if line.last() != Some(&b'\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid line in response"));
+    }
+    
+    while matches!(line.last(), Some(b'\n' | b'\r')) {
+        line.pop();
+    }
+    
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+// read and write timeouts are reported as would block on some platforms
+fn timed_out(error: io::Error) -> io::Error {
+    match error.kind() {
+        io::ErrorKind::WouldBlock => io::Error::new(io::ErrorKind::TimedOut, error),
+        _ => error,
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    env,
+    io,
+    str,
+};
+
+use super::Url;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProxyScheme {
+    Http,
+    Socks5,
+}
+
+// proxy used for each scheme of the requested URLs, none meaning a direct connection
+#[derive(Clone)]
+pub struct Proxy {
+    http: Option<Endpoint>,
+    https: Option<Endpoint>,
+    bypass: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Endpoint {
+    scheme: ProxyScheme,
+    host: String,
+    port: u16,
+    credentials: Option<(String, String)>,
+}
+
+impl Proxy {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn parse(url: &str) -> io::Result<Self> {
+        let endpoint = Endpoint::parse(url)?;
+        
+        Ok(Self {
+            http: Some(endpoint.clone()),
+            https: Some(endpoint),
+            bypass: Vec::new(),
+        })
+    }
+    
+    pub fn from_env() -> io::Result<Option<Self>> {
+        
+        fn read(names: &[&str]) -> Option<String> {
+            names.iter()
+                .find_map(|name| env::var(name).ok())
+                .filter(|value| ! value.trim().is_empty())
+        }
+        
+        let fallback = read(&["ALL_PROXY", "all_proxy"]);
+        
+        let http = read(&["HTTP_PROXY", "http_proxy"]).or_else(|| fallback.clone());
+        let https = read(&["HTTPS_PROXY", "https_proxy"]).or(fallback);
+        
+        if http.is_none() && https.is_none() {
+            return Ok(None);
+        }
+        
+        let mut proxy = Self {
+            http: http.as_deref().map(Endpoint::parse).transpose()?,
+            https: https.as_deref().map(Endpoint::parse).transpose()?,
+            bypass: Vec::new(),
+        };
+        
+        if let Some(bypass) = read(&["NO_PROXY", "no_proxy"]) {
+            proxy = proxy.with_bypass(&bypass);
+        }
+        
+        Ok(Some(proxy))
+        
+    }
+    
+    pub fn with_bypass(mut self, bypass: &str) -> Self {
+        self.bypass.extend(bypass.split([',', ';', ' '])
+            .map(str::trim)
+            .filter(|entry| ! entry.is_empty())
+            .map(str::to_owned));
+        self
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub(crate) fn endpoint(&self, scheme: &str) -> Option<&Endpoint> {
+        match scheme {
+            "http" => self.http.as_ref(),
+            "https" => self.https.as_ref(),
+            _ => None,
+        }
+    }
+    
+    pub(crate) fn endpoints(&self) -> impl Iterator<Item = (&'static str, &Endpoint)> {
+        [("http", self.http.as_ref()), ("https", self.https.as_ref())]
+            .into_iter()
+            .filter_map(|(scheme, endpoint)| Some((scheme, endpoint?)))
+    }
+    
+    // "no_proxy" conventions, for backends matching hosts themselves
+    pub(crate) fn bypasses(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        
+        self.bypass.iter().any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            
+            let host = host.to_ascii_lowercase();
+            let entry = entry.to_ascii_lowercase();
+            
+            match entry.strip_prefix("*.").or_else(|| entry.strip_prefix('.')) {
+                Some(domain) => host.ends_with(&format!(".{}", domain)),
+                None => host == entry || host.ends_with(&format!(".{}", entry)),
+            }
+        })
+    }
+    
+    pub(crate) fn bypass_list(&self) -> String {
+        // "no_proxy" conventions translated to the ones used by WinHTTP
+        
+        let mut list = Vec::with_capacity(self.bypass.len() * 2);
+        
+        for entry in &self.bypass {
+            if entry == "*" {
+                list.push(String::from("*"));
+            } else if let Some(domain) = entry.strip_prefix("*.").or_else(|| entry.strip_prefix('.')) {
+                list.push(format!("*.{}", domain));
+            } else {
+                list.push(entry.clone());
+                list.push(format!("*.{}", entry));
+            }
+        }
+        
+        list.join(";")
+    }
+    
+}
+
+impl Endpoint {
+    
+    // -------------------- constructors --------------------
+    
+    
+    fn parse(url: &str) -> io::Result<Self> {
+        let url = url.trim();
+        
+        // a bare address is taken as an http proxy
+        let url = if url.contains("://") {
+            Url::parse(url)?
+        } else {
+            Url::parse(&format!("http://{}", url))?
+        };
+        
+        let scheme = match url.scheme() {
+            "http" => ProxyScheme::Http,
+            // host names are always resolved by the proxy
+            "socks5" | "socks5h" => ProxyScheme::Socks5,
+            _ => return Err(io::Error::new(io::ErrorKind::Unsupported, "Unsupported proxy scheme")),
+        };
+        
+        let credentials = url.username().map(|username| (username, url.password().unwrap_or_default()));
+        
+        Ok(Self {
+            scheme,
+            host: url.host().to_owned(),
+            port: url.port().unwrap_or_default(),
+            credentials,
+        })
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub(crate) fn scheme(&self) -> ProxyScheme {
+        self.scheme
+    }
+    
+    pub(crate) fn host(&self) -> &str {
+        &self.host
+    }
+    
+    pub(crate) fn port(&self) -> u16 {
+        self.port
+    }
+    
+    pub(crate) fn credentials(&self) -> Option<(&str, &str)> {
+        self.credentials.as_ref().map(|(username, password)| (username.as_str(), password.as_str()))
+    }
+    
+}
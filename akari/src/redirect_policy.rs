@@ -0,0 +1,62 @@
+use std::io;
+
+use super::Url;
+
+const DEFAULT_MAX_HOPS: u8 = 10;
+
+#[derive(Clone, Copy)]
+pub struct RedirectPolicy {
+    pub max_hops: u8,
+    pub same_scheme: bool,
+}
+
+impl Default for RedirectPolicy {
+    
+    fn default() -> Self {
+        Self {
+            max_hops: DEFAULT_MAX_HOPS,
+            same_scheme: false,
+        }
+    }
+    
+}
+
+impl RedirectPolicy {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn none() -> Self {
+        Self {
+            max_hops: 0,
+            same_scheme: true,
+        }
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    pub(crate) fn follow(&self, current: &Url, location: &str, hops: u8) -> io::Result<Url> {
+        if hops > self.max_hops {
+            return Err(io::Error::other("Maximum number of redirects exceeded"));
+        }
+        
+        let next = current.join(location)?;
+        
+        if ! matches!(next.scheme(), "http" | "https") {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Redirect to an unsupported scheme"));
+        }
+        
+        let secure_current = current.is_secure();
+        let secure_next = next.is_secure();
+        
+        // downgrades are never followed
+        if (secure_current && ! secure_next) || (self.same_scheme && secure_current != secure_next) {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, "Redirect to a different scheme"));
+        }
+        
+        Ok(next)
+    }
+    
+}
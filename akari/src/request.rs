@@ -0,0 +1,120 @@
+use std::{
+    fs,
+    io::{ self, BufWriter, Write },
+    path::Path,
+};
+
+use super::{
+    Client, Payload, Timeouts, Cache,
+    payload::Progress,
+};
+
+const DOWNLOAD_WRITER_BUFFER_SIZE: usize = 64 * 1024;
+
+pub struct RequestBuilder<'c> {
+    client: &'c mut Client,
+    url: &'c str,
+    headers: String,
+    timeouts: Option<Timeouts>,
+    range_from: Option<u64>,
+    size_limit: Option<u64>,
+    progress: Option<Progress>,
+}
+
+impl<'c> RequestBuilder<'c> {
+    
+    pub(crate) fn new(client: &'c mut Client, url: &'c str) -> Self {
+        Self {
+            client,
+            url,
+            headers: String::new(),
+            timeouts: None,
+            range_from: None,
+            size_limit: None,
+            progress: None,
+        }
+    }
+    
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = Some(timeouts);
+        self
+    }
+    
+    pub fn with_cache(mut self, cache: &Cache) -> Self {
+        if let Some(validators) = cache.get(self.url) {
+            self.headers.push_str(&validators.headers());
+        }
+        self
+    }
+    
+    pub fn with_range_from(mut self, offset: u64) -> Self {
+        self.range_from = Some(offset);
+        self
+    }
+    
+    pub fn with_size_limit(mut self, size_limit: u64) -> Self {
+        self.size_limit = Some(size_limit);
+        self
+    }
+    
+    pub fn with_progress(mut self, progress: impl FnMut(u64, Option<u64>) + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+    
+    pub fn send(self) -> io::Result<Payload> {
+        let mut headers = self.headers;
+        
+        // byte ranges of an encoded body would refer to the encoded representation
+        match self.range_from {
+            Some(offset) => headers.push_str(&format!("Range: bytes={}-\r\n", offset)),
+            None => headers.push_str("Accept-Encoding: gzip, deflate\r\n"),
+        }
+        
+        self.client.execute(self.url, &headers, self.timeouts.as_ref(), self.range_from.is_some())?
+            .with_size_limit(self.size_limit)
+            .map(|payload| payload.with_progress(self.progress))
+    }
+    
+    pub fn download(mut self, path: impl AsRef<Path>) -> io::Result<u64> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        
+        // any existing content is taken as a previous partial download
+        let existing = file.metadata()?.len();
+        
+        if existing > 0 {
+            self.range_from = Some(existing);
+        }
+        
+        let mut payload = self.send()?;
+        
+        match payload.status() {
+            
+            // nothing left to download
+            304 | 416 => return Ok(existing),
+            
+            206 => {
+                if payload.offset() != existing {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Unexpected content range"));
+                }
+            },
+            
+            // the range was ignored, so the complete body is sent
+            _ => file.set_len(0)?,
+            
+        }
+        
+        let mut writer = BufWriter::with_capacity(DOWNLOAD_WRITER_BUFFER_SIZE, &mut file);
+        
+        io::copy(&mut payload, &mut writer)?;
+        
+        writer.flush()?;
+        drop(writer);
+        
+        Ok(file.metadata()?.len())
+    }
+    
+}
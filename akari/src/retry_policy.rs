@@ -0,0 +1,67 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{ BuildHasher, Hasher },
+    time::Duration,
+};
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+    
+}
+
+impl RetryPolicy {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_delay);
+        }
+        
+        // exponential backoff with equal jitter
+        
+        let ceiling = self.base_delay
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        
+        let half = ceiling / 2;
+        
+        let jitter = match u64::try_from(half.as_millis()).unwrap_or(u64::MAX) {
+            0 => 0,
+            range => RandomState::new().build_hasher().finish() % (range + 1),
+        };
+        
+        half + Duration::from_millis(jitter)
+    }
+    
+}
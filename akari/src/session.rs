@@ -5,12 +5,7 @@ use std::{
     ptr,
 };
 
-use super::ffi;
-
-const DNS_RESOLUTION_TIMEOUT_AS_MILLIS: c_int = 15_000;
-const CONNECTION_TIMEOUT_AS_MILLIS: c_int = 15_000;
-const SEND_TIMEOUT_AS_MILLIS: c_int = 15_000;
-const RECEIVE_TIMEOUT_AS_MILLIS: c_int = 15_000;
+use super::{ ffi, Timeouts };
 
 pub struct Session {
     pub handle: ffi::HINTERNET,
@@ -18,16 +13,21 @@ pub struct Session {
 
 impl Session {
     
-    pub fn new() -> io::Result<Self> {
+    pub fn new(user_agent: &str, timeouts: &Timeouts, proxy: Option<(&str, &str)>) -> io::Result<Self> {
         // -------------------- handle --------------------
         
         let handle = unsafe {
             
+            let name = proxy.map(|(name, _)| chikuwa::WinString::from(name));
+            let bypass = proxy.map(|(_, bypass)| bypass)
+                .filter(|bypass| ! bypass.is_empty())
+                .map(chikuwa::WinString::from);
+            
             let result = ffi::WinHttpOpen(
-                chikuwa::WinString::from(env!("CARGO_PKG_NAME")).as_ptr(),
-                ffi::WINHTTP_ACCESS_TYPE_DEFAULT_PROXY,
-                ffi::WINHTTP_NO_PROXY_NAME,
-                ffi::WINHTTP_NO_PROXY_BYPASS,
+                chikuwa::WinString::from(user_agent).as_ptr(),
+                if name.is_some() { ffi::WINHTTP_ACCESS_TYPE_NAMED_PROXY } else { ffi::WINHTTP_ACCESS_TYPE_DEFAULT_PROXY },
+                name.as_ref().map_or(ffi::WINHTTP_NO_PROXY_NAME, |name| name.as_ptr()),
+                bypass.as_ref().map_or(ffi::WINHTTP_NO_PROXY_BYPASS, |bypass| bypass.as_ptr()),
                 0,
             );
             
@@ -41,22 +41,9 @@ impl Session {
         
         // -------------------- timeouts --------------------
         
-        unsafe {
-            
-            let result = ffi::WinHttpSetTimeouts(
-                handle,
-                DNS_RESOLUTION_TIMEOUT_AS_MILLIS,
-                CONNECTION_TIMEOUT_AS_MILLIS,
-                SEND_TIMEOUT_AS_MILLIS,
-                RECEIVE_TIMEOUT_AS_MILLIS,
-            );
-            
-            if result == 0 {
-                let error = Err(io::Error::last_os_error());
-                ffi::WinHttpCloseHandle(handle);
-                return error;
-            }
-            
+        if let Err(error) = timeouts.apply(handle) {
+            unsafe { ffi::WinHttpCloseHandle(handle) };
+            return Err(error);
         }
         
         // -------------------- http version --------------------
@@ -0,0 +1,384 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{ BuildHasher, Hasher },
+    io::{ self, Read, Write },
+    net::{ IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream },
+    str,
+    sync::{
+        Arc, Mutex,
+        atomic::{ AtomicBool, Ordering },
+    },
+    thread::{ self, JoinHandle },
+};
+
+use super::{ proxy::Endpoint, Url };
+
+// https://www.rfc-editor.org/rfc/rfc1928
+// https://www.rfc-editor.org/rfc/rfc1929
+
+const REQUEST_HEAD_SIZE_LIMIT: usize = 64 * 1024;
+const USERNAME: &str = "akari";
+
+// WinHTTP cannot talk to SOCKS proxies, so it is pointed to a local HTTP proxy relaying every connection through one
+pub struct Bridge {
+    address: SocketAddr,
+    password: String,
+    stopped: Arc<AtomicBool>,
+    listener: Option<JoinHandle<()>>,
+}
+
+// sockets of a relayed connection, shut down to unblock its thread when the bridge stops
+type Sockets = Arc<Mutex<Vec<TcpStream>>>;
+
+impl Bridge {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn start(proxy: &Endpoint) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        
+        // any local process can reach the listener, so only requests carrying this secret are relayed
+        let password = (0..2)
+            .map(|_| format!("{:016x}", RandomState::new().build_hasher().finish()))
+            .collect::<String>();
+        
+        let proxy = Arc::new(proxy.clone());
+        let authorization = Arc::new(format!("Basic {}", encode_base64(format!("{}:{}", USERNAME, password).as_bytes())));
+        let flag = Arc::clone(&stopped);
+        
+        let listener = thread::spawn(move || {
+            let mut connections: Vec<(Sockets, JoinHandle<()>)> = Vec::new();
+            
+            for stream in listener.incoming() {
+                
+                if flag.load(Ordering::Acquire) {
+                    break;
+                }
+                
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                
+                connections.retain(|(_, connection)| ! connection.is_finished());
+                
+                let Ok(clone) = stream.try_clone() else {
+                    continue;
+                };
+                
+                let sockets = Arc::new(Mutex::new(vec![clone]));
+                let proxy = Arc::clone(&proxy);
+                let authorization = Arc::clone(&authorization);
+                let registered = Arc::clone(&sockets);
+                
+                let connection = thread::spawn(move || {
+                    handle(stream, &proxy, &authorization, &registered).ok();
+                });
+                
+                connections.push((sockets, connection));
+                
+            }
+            
+            for (sockets, connection) in connections {
+                for socket in sockets.lock().unwrap_or_else(|error| error.into_inner()).iter() {
+                    socket.shutdown(Shutdown::Both).ok();
+                }
+                connection.join().ok();
+            }
+        });
+        
+        Ok(Self {
+            address,
+            password,
+            stopped,
+            listener: Some(listener),
+        })
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+    
+    pub fn credentials(&self) -> (&str, &str) {
+        (USERNAME, &self.password)
+    }
+    
+}
+
+impl Drop for Bridge {
+    
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        
+        // wake up the listener so that it notices the flag
+        TcpStream::connect(self.address).ok();
+        
+        if let Some(listener) = self.listener.take() {
+            listener.join().ok();
+        }
+    }
+    
+}
+
+fn handle(mut client: TcpStream, proxy: &Endpoint, authorization: &str, sockets: &Sockets) -> io::Result<()> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid proxy request");
+    
+    // -------------------- head --------------------
+    
+    let mut head = Vec::new();
+    let mut buffer = [0; 4096];
+    
+    let end = loop {
+        
+        let end = loop {
+            
+            if let Some(position) = head.windows(4).position(|window| window == b"\r\n\r\n") {
+                break position + 4;
+            }
+            
+            let bytes = client.read(&mut buffer)?;
+            
+            if bytes == 0 || head.len() + bytes > REQUEST_HEAD_SIZE_LIMIT {
+                return Err(invalid());
+            }
+            
+            head.extend_from_slice(&buffer[..bytes]);
+            
+        };
+        
+        if is_authorized(&head[..end], authorization) {
+            break end;
+        }
+        
+        // the connection is kept open for the request to be sent again with credentials
+        client.write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\nProxy-Authenticate: Basic realm=\"akari\"\r\nContent-Length: 0\r\n\r\n")?;
+        head.drain(..end);
+        
+    };
+    
+    let line_end = head.windows(2).position(|window| window == b"\r\n").ok_or_else(invalid)?;
+    let line = str::from_utf8(&head[..line_end]).map_err(|_| invalid())?;
+    
+    let mut parts = line.split(' ');
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    
+    // -------------------- tunnel --------------------
+    
+    let mut upstream = if method.eq_ignore_ascii_case("CONNECT") {
+        
+        let (host, port) = split_address(target, 443).ok_or_else(invalid)?;
+        let mut upstream = connect(proxy, host, port, Some(sockets))?;
+        
+        client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")?;
+        
+        // anything sent ahead of the response belongs to the tunnel
+        upstream.write_all(&head[end..])?;
+        
+        upstream
+        
+    } else {
+        
+        // absolute-form request targets are rewritten to origin-form
+        let url = Url::parse(target).map_err(|_| invalid())?;
+        
+        if url.scheme() != "http" {
+            return Err(invalid());
+        }
+        
+        let mut upstream = connect(proxy, url.connect_host(), url.port().unwrap_or_default(), Some(sockets))?;
+        
+        let mut rewritten = Vec::with_capacity(head.len());
+        
+        rewritten.extend_from_slice(format!("{} {} {}\r\n", method, url.request_target(), version).as_bytes());
+        
+        // the tunnel is bound to a single host, so the connection cannot be reused, and the secret must not reach it
+        for header in head[line_end + 2..end - 2].split(|&byte| byte == b'\n') {
+            let name = header.split(|&byte| byte == b':').next().unwrap_or_default();
+            if ! [&b"connection"[..], b"proxy-connection", b"proxy-authorization"].iter().any(|excluded| name.eq_ignore_ascii_case(excluded)) && ! header.is_empty() {
+                rewritten.extend_from_slice(header);
+                rewritten.push(b'\n');
+            }
+        }
+        
+        rewritten.extend_from_slice(b"Connection: close\r\n\r\n");
+        rewritten.extend_from_slice(&head[end..]);
+        
+        upstream.write_all(&rewritten)?;
+        
+        upstream
+        
+    };
+    
+    // -------------------- relay --------------------
+    
+    let mut client_reader = client.try_clone()?;
+    let mut upstream_writer = upstream.try_clone()?;
+    
+    let outgoing = thread::spawn(move || {
+        io::copy(&mut client_reader, &mut upstream_writer).ok();
+        upstream_writer.shutdown(Shutdown::Write).ok();
+    });
+    
+    io::copy(&mut upstream, &mut client).ok();
+    client.shutdown(Shutdown::Both).ok();
+    
+    outgoing.join().ok();
+    
+    Ok(())
+}
+
+// sockets of a relayed connection are registered as soon as opened, so that the negotiation can be interrupted as well
+pub(crate) fn connect(proxy: &Endpoint, host: &str, port: u16, sockets: Option<&Sockets>) -> io::Result<TcpStream> {
+    let failed = |message: &str| io::Error::new(io::ErrorKind::ConnectionRefused, format!("SOCKS proxy error: {}", message));
+    
+    let mut stream = TcpStream::connect((proxy.host().trim_start_matches('[').trim_end_matches(']'), proxy.port()))?;
+    
+    if let Some(sockets) = sockets {
+        sockets.lock().unwrap_or_else(|error| error.into_inner()).push(stream.try_clone()?);
+    }
+    
+    // -------------------- negotiation --------------------
+    
+    if proxy.credentials().is_some() {
+        stream.write_all(&[5, 2, 0, 2])?;
+    } else {
+        stream.write_all(&[5, 1, 0])?;
+    }
+    
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply)?;
+    
+    match (reply, proxy.credentials()) {
+        
+        ([5, 0], _) => (),
+        
+        ([5, 2], Some((username, password))) => {
+            
+            let username_length = u8::try_from(username.len()).map_err(|_| failed("username too long"))?;
+            let password_length = u8::try_from(password.len()).map_err(|_| failed("password too long"))?;
+            
+            let mut request = Vec::with_capacity(3 + username.len() + password.len());
+            
+            request.push(1);
+            request.push(username_length);
+            request.extend_from_slice(username.as_bytes());
+            request.push(password_length);
+            request.extend_from_slice(password.as_bytes());
+            
+            stream.write_all(&request)?;
+            stream.read_exact(&mut reply)?;
+            
+            if reply[1] != 0 {
+                return Err(failed("authentication rejected"));
+            }
+            
+        },
+        
+        _ => return Err(failed("no acceptable authentication method")),
+        
+    }
+    
+    // -------------------- connection --------------------
+    
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    
+    let mut request = vec![5, 1, 0];
+    
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(address)) => {
+            request.push(1);
+            request.extend_from_slice(&address.octets());
+        },
+        Ok(IpAddr::V6(address)) => {
+            request.push(4);
+            request.extend_from_slice(&address.octets());
+        },
+        Err(_) => {
+            request.push(3);
+            request.push(u8::try_from(host.len()).map_err(|_| failed("host name too long"))?);
+            request.extend_from_slice(host.as_bytes());
+        },
+    }
+    
+    request.extend_from_slice(&port.to_be_bytes());
+    
+    stream.write_all(&request)?;
+    
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply)?;
+    
+    if reply[1] != 0 {
+        return Err(failed(match reply[1] {
+            2 => "connection not allowed by ruleset",
+            3 => "network unreachable",
+            4 => "host unreachable",
+            5 => "connection refused",
+            6 => "TTL expired",
+            _ => "general failure",
+        }));
+    }
+    
+    // bound address is of no use, but must be consumed
+    let length = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => {
+            let mut length = [0];
+            stream.read_exact(&mut length)?;
+            usize::from(length[0])
+        },
+        _ => return Err(failed("invalid address type")),
+    };
+    
+    let mut bound = vec![0; length + 2];
+    stream.read_exact(&mut bound)?;
+    
+    Ok(stream)
+}
+
+fn is_authorized(head: &[u8], authorization: &str) -> bool {
+    head.split(|&byte| byte == b'\n')
+        .filter_map(|header| str::from_utf8(header).ok())
+        .filter_map(|header| header.split_once(':'))
+        .any(|(name, value)| name.trim().eq_ignore_ascii_case("proxy-authorization") && value.trim() == authorization)
+}
+
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    
+    for chunk in bytes.chunks(3) {
+        
+        let group = chunk.iter()
+            .enumerate()
+            .fold(0_u32, |group, (index, &byte)| group | u32::from(byte) << (16 - index * 8));
+        
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(char::from(ALPHABET[(group >> (18 - index * 6)) as usize & 63]));
+            } else {
+                encoded.push('=');
+            }
+        }
+        
+    }
+    
+    encoded
+}
+
+fn split_address(address: &str, default_port: u16) -> Option<(&str, u16)> {
+    match address.rfind(':') {
+        Some(index) if ! address[index..].contains(']') => Some((&address[..index], address[index + 1..].parse().ok()?)),
+        _ => Some((address, default_port)),
+    }
+    .filter(|(host, _)| ! host.is_empty())
+}
@@ -0,0 +1,71 @@
+use std::{
+    io,
+    os::raw::*,
+    time::Duration,
+};
+
+use super::ffi;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy)]
+pub struct Timeouts {
+    pub resolve: Duration,
+    pub connect: Duration,
+    pub send: Duration,
+    pub receive: Duration,
+}
+
+impl Default for Timeouts {
+    
+    fn default() -> Self {
+        Self::uniform(DEFAULT_TIMEOUT)
+    }
+    
+}
+
+impl Timeouts {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn uniform(timeout: Duration) -> Self {
+        Self {
+            resolve: timeout,
+            connect: timeout,
+            send: timeout,
+            receive: timeout,
+        }
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    pub(crate) fn apply(&self, handle: ffi::HINTERNET) -> io::Result<()> {
+        
+        fn as_millis(timeout: Duration) -> c_int {
+            c_int::try_from(timeout.as_millis()).unwrap_or(c_int::MAX)
+        }
+        
+        unsafe {
+            
+            let result = ffi::WinHttpSetTimeouts(
+                handle,
+                as_millis(self.resolve),
+                as_millis(self.connect),
+                as_millis(self.send),
+                as_millis(self.receive),
+            );
+            
+            if result == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            
+        }
+        
+        Ok(())
+        
+    }
+    
+}
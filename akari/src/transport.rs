@@ -0,0 +1,36 @@
+use std::io::{ self, Read };
+
+use super::{ Url, Timeouts, PoolStats };
+
+// network backend of clients not given a transport of their own
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    WinHttp,
+    // plain HTTP/1.1 over the standard library, without TLS nor HTTP/2
+    Portable,
+}
+
+pub trait Transport {
+    
+    fn send(&mut self, request: &Request) -> io::Result<Response>;
+    
+    fn pool_stats(&self) -> PoolStats {
+        PoolStats::default()
+    }
+    
+}
+
+pub struct Request<'r> {
+    pub url: &'r Url,
+    // "name: value" lines, each terminated by CRLF
+    pub headers: &'r str,
+    pub timeouts: Option<&'r Timeouts>,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    // as transferred, so still encoded if the server applied a content encoding
+    pub body: Box<dyn Read>,
+}
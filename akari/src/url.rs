@@ -0,0 +1,399 @@
+use std::{
+    fmt,
+    io,
+    net::Ipv6Addr,
+    str::{ self, FromStr },
+};
+
+// https://www.rfc-editor.org/rfc/rfc3986
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Url {
+    scheme: String,
+    userinfo: Option<String>,
+    host: String,
+    port: Option<u16>,
+    path: String,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+struct Reference<'r> {
+    scheme: Option<&'r str>,
+    authority: Option<&'r str>,
+    path: &'r str,
+    query: Option<&'r str>,
+    fragment: Option<&'r str>,
+}
+
+#[derive(Clone, Copy)]
+enum Component {
+    Userinfo,
+    Path,
+    Query,
+}
+
+impl Url {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn parse(input: &str) -> io::Result<Self> {
+        let reference = Reference::split(input.trim());
+        
+        let scheme = reference.scheme.ok_or_else(|| invalid("Missing scheme"))?;
+        let authority = reference.authority.ok_or_else(|| invalid("Missing authority"))?;
+        
+        Self::build(scheme, authority, reference.path, reference.query, reference.fragment)
+    }
+    
+    pub fn join(&self, input: &str) -> io::Result<Self> {
+        let reference = Reference::split(input.trim());
+        
+        // https://www.rfc-editor.org/rfc/rfc3986#section-5.2.2
+        
+        if let Some(scheme) = reference.scheme {
+            let authority = reference.authority.ok_or_else(|| invalid("Missing authority"))?;
+            return Self::build(scheme, authority, reference.path, reference.query, reference.fragment);
+        }
+        
+        if let Some(authority) = reference.authority {
+            return Self::build(&self.scheme, authority, reference.path, reference.query, reference.fragment);
+        }
+        
+        let mut url = self.clone();
+        
+        url.fragment = reference.fragment.map(|fragment| encode(fragment, Component::Query));
+        
+        if reference.path.is_empty() {
+            if let Some(query) = reference.query {
+                url.query = Some(encode(query, Component::Query));
+            }
+            return Ok(url);
+        }
+        
+        let path = if reference.path.starts_with('/') {
+            encode(reference.path, Component::Path)
+        } else {
+            let directory = &self.path[..self.path.rfind('/').map_or(0, |index| index + 1)];
+            format!("{}{}", directory, encode(reference.path, Component::Path))
+        };
+        
+        url.path = remove_dot_segments(&path);
+        url.query = reference.query.map(|query| encode(query, Component::Query));
+        
+        Ok(url)
+    }
+    
+    fn build(scheme: &str, authority: &str, path: &str, query: Option<&str>, fragment: Option<&str>) -> io::Result<Self> {
+        // -------------------- scheme --------------------
+        
+        let valid = scheme.chars().next().is_some_and(|first| first.is_ascii_alphabetic())
+            && scheme.chars().all(|current| current.is_ascii_alphanumeric() || matches!(current, '+' | '-' | '.'));
+        
+        if ! valid {
+            return Err(invalid("Invalid scheme"));
+        }
+        
+        let scheme = scheme.to_ascii_lowercase();
+        
+        // -------------------- authority --------------------
+        
+        let (userinfo, address) = match authority.rsplit_once('@') {
+            Some((userinfo, address)) => (Some(encode(userinfo, Component::Userinfo)), address),
+            None => (None, authority),
+        };
+        
+        let (host, port) = match address.rfind(':') {
+            Some(index) if ! address[index..].contains(']') => (&address[..index], &address[index + 1..]),
+            _ => (address, ""),
+        };
+        
+        let host = if let Some(literal) = host.strip_prefix('[') {
+            let literal = literal.strip_suffix(']').ok_or_else(|| invalid("Invalid host"))?;
+            let address = literal.parse::<Ipv6Addr>().map_err(|_| invalid("Invalid host"))?;
+            format!("[{}]", address)
+        } else if host.is_empty() || host.contains(|current: char| current.is_whitespace() || "/?#[]@\\%".contains(current)) {
+            return Err(invalid("Invalid host"));
+        } else {
+            host.to_lowercase()
+        };
+        
+        // an empty port is equivalent to the default one
+        let port = match port {
+            "" => None,
+            port => Some(port.parse::<u16>().map_err(|_| invalid("Invalid port"))?),
+        };
+        
+        let port = port.filter(|&port| Some(port) != default_port(&scheme));
+        
+        // -------------------- path, query and fragment --------------------
+        
+        let path = match path {
+            "" => String::from("/"),
+            path => remove_dot_segments(&encode(path, Component::Path)),
+        };
+        
+        Ok(Self {
+            scheme,
+            userinfo,
+            host,
+            port,
+            path,
+            query: query.map(|query| encode(query, Component::Query)),
+            fragment: fragment.map(|fragment| encode(fragment, Component::Query)),
+        })
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+    
+    pub fn is_secure(&self) -> bool {
+        self.scheme == "https"
+    }
+    
+    pub fn username(&self) -> Option<String> {
+        self.userinfo.as_deref()
+            .map(|userinfo| userinfo.split(':').next().unwrap_or_default())
+            .map(decode)
+    }
+    
+    pub fn password(&self) -> Option<String> {
+        self.userinfo.as_deref()
+            .and_then(|userinfo| userinfo.split_once(':'))
+            .map(|(_, password)| decode(password))
+    }
+    
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+    
+    pub fn port(&self) -> Option<u16> {
+        self.port.or_else(|| default_port(&self.scheme))
+    }
+    
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+    
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+    
+    pub fn request_target(&self) -> String {
+        match &self.query {
+            Some(query) => format!("{}?{}", self.path, query),
+            None => self.path.clone(),
+        }
+    }
+    
+    pub(crate) fn connect_host(&self) -> &str {
+        self.host.trim_start_matches('[').trim_end_matches(']')
+    }
+    
+}
+
+impl FromStr for Url {
+    
+    type Err = io::Error;
+    
+    fn from_str(input: &str) -> io::Result<Self> {
+        Self::parse(input)
+    }
+    
+}
+
+impl fmt::Display for Url {
+    
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}://", self.scheme)?;
+        
+        if let Some(userinfo) = &self.userinfo {
+            write!(f, "{}@", userinfo)?;
+        }
+        
+        f.write_str(&self.host)?;
+        
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        
+        f.write_str(&self.request_target())?;
+        
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", fragment)?;
+        }
+        
+        Ok(())
+    }
+    
+}
+
+impl<'r> Reference<'r> {
+    
+    // https://www.rfc-editor.org/rfc/rfc3986#appendix-B
+    fn split(input: &'r str) -> Self {
+        let (rest, fragment) = match input.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (input, None),
+        };
+        
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        
+        let (scheme, rest) = match rest.find(':') {
+            Some(index) if ! rest[..index].contains('/') && index > 0 => (Some(&rest[..index]), &rest[index + 1..]),
+            _ => (None, rest),
+        };
+        
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => match rest.find('/') {
+                Some(index) => (Some(&rest[..index]), &rest[index..]),
+                None => (Some(rest), ""),
+            },
+            None => (None, rest),
+        };
+        
+        Self {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+    
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" => Some(80),
+        "https" => Some(443),
+        "socks5" | "socks5h" => Some(1080),
+        _ => None,
+    }
+}
+
+fn encode(input: &str, component: Component) -> String {
+    let bytes = input.as_bytes();
+    let mut encoded = String::with_capacity(bytes.len());
+    let mut index = 0;
+    
+    while index < bytes.len() {
+        
+        let byte = bytes[index];
+        
+        // existing escapes are preserved, with unreserved characters decoded and hexadecimal digits uppercased
+        if byte == b'%' {
+            if let Some(value) = bytes.get(index + 1..index + 3).and_then(parse_hex) {
+                if is_unreserved(value) {
+                    encoded.push(char::from(value));
+                } else {
+                    encoded.push_str(&format!("%{:02X}", value));
+                }
+                index += 3;
+                continue;
+            }
+        }
+        
+        let allowed = is_unreserved(byte) || match component {
+            Component::Userinfo => b"!$&'()*+,;=:".contains(&byte),
+            Component::Path => b"!$&'()*+,;=:@/".contains(&byte),
+            Component::Query => b"!$&'()*+,;=:@/?".contains(&byte),
+        };
+        
+        if allowed {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+        
+        index += 1;
+        
+    }
+    
+    encoded
+}
+
+fn decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    
+    while index < bytes.len() {
+        
+        if bytes[index] == b'%' {
+            if let Some(value) = bytes.get(index + 1..index + 3).and_then(parse_hex) {
+                decoded.push(value);
+                index += 3;
+                continue;
+            }
+        }
+        
+        decoded.push(bytes[index]);
+        index += 1;
+        
+    }
+    
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_hex(digits: &[u8]) -> Option<u8> {
+    str::from_utf8(digits).ok()
+        .filter(|digits| digits.bytes().all(|digit| digit.is_ascii_hexdigit()))
+        .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4
+fn remove_dot_segments(path: &str) -> String {
+    let mut output: Vec<&str> = Vec::new();
+    
+    let segments: Vec<&str> = path.split('/').collect();
+    let last = segments.len() - 1;
+    
+    for (index, segment) in segments.iter().enumerate() {
+        match *segment {
+            "." => {
+                if index == last {
+                    output.push("");
+                }
+            },
+            ".." => {
+                if output.len() > 1 {
+                    output.pop();
+                }
+                if index == last {
+                    output.push("");
+                }
+            },
+            segment => output.push(segment),
+        }
+    }
+    
+    let joined = output.join("/");
+    
+    if joined.starts_with('/') {
+        joined
+    } else {
+        format!("/{}", joined)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
@@ -0,0 +1,369 @@
+use std::{
+    io::{ self, Read },
+    mem,
+    os::raw::*,
+    ptr,
+    time::Duration,
+};
+
+use super::{
+    ffi, Session, Connection, Pool, PoolStats, Bridge, Proxy, ProxyScheme, Timeouts,
+    proxy::Endpoint,
+    transport::{ Transport, Request, Response },
+};
+
+pub struct WinHttp {
+    // connection handles must be closed before the session they belong to
+    pool: Pool,
+    session: Session,
+    proxy: Option<Proxy>,
+    bridges: Vec<(Endpoint, Bridge)>,
+}
+
+struct Body {
+    handle: ffi::HINTERNET,
+}
+
+impl WinHttp {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new(user_agent: &str, timeouts: &Timeouts, idle_timeout: Option<Duration>, proxy: Option<Proxy>) -> io::Result<Self> {
+        let mut bridges: Vec<(Endpoint, Bridge)> = Vec::new();
+        let mut names = Vec::new();
+        
+        // one entry per scheme, requests of a scheme without any being sent directly
+        for (scheme, endpoint) in proxy.iter().flat_map(Proxy::endpoints) {
+            
+            let address = if endpoint.scheme() == ProxyScheme::Socks5 {
+                
+                let index = match bridges.iter().position(|(started, _)| started == endpoint) {
+                    Some(index) => index,
+                    None => {
+                        bridges.push((endpoint.clone(), Bridge::start(endpoint)?));
+                        bridges.len() - 1
+                    },
+                };
+                
+                bridges[index].1.address().to_string()
+                
+            } else {
+                format!("{}:{}", endpoint.host(), endpoint.port())
+            };
+            
+            names.push(format!("{}={}", scheme, address));
+            
+        }
+        
+        let name = (! names.is_empty()).then(|| names.join(" "));
+        
+        let bypass = proxy.as_ref()
+            .map(Proxy::bypass_list)
+            .unwrap_or_default();
+        
+        Ok(Self {
+            pool: idle_timeout.map(Pool::new).unwrap_or_default(),
+            session: Session::new(user_agent, timeouts, name.as_deref().map(|name| (name, bypass.as_str())))?,
+            proxy,
+            bridges,
+        })
+    }
+    
+}
+
+impl Transport for WinHttp {
+    
+    fn send(&mut self, request: &Request) -> io::Result<Response> {
+        let host = request.url.connect_host();
+        let port = request.url.port().unwrap_or_default();
+        
+        let endpoint = self.proxy.as_ref().and_then(|proxy| proxy.endpoint(request.url.scheme()));
+        
+        // the local relay of a SOCKS proxy has its own credentials, the proxy ones being used by the relay itself
+        let credentials = endpoint.and_then(|endpoint| match self.bridges.iter().find(|(started, _)| started == endpoint) {
+            Some((_, bridge)) => Some(bridge.credentials()),
+            None => endpoint.credentials(),
+        });
+        
+        let result = self.pool.acquire(&self.session, host, port)
+            .and_then(|connection| open(connection, request, credentials));
+        
+        // a handle that failed is not trusted to be reused
+        if result.is_err() {
+            self.pool.discard(host, port);
+        }
+        
+        result
+    }
+    
+    fn pool_stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
+    
+}
+
+impl Read for Body {
+    
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        unsafe {
+            
+            let mut amount_read: c_ulong = 0;
+            
+            #[allow(clippy::cast_possible_truncation)]
+            let bytes = buf.len() as c_ulong;
+            
+            let result = ffi::WinHttpReadData(
+                self.handle,
+                ptr::from_mut(buf).cast::<c_void>(),
+                bytes,
+                &mut amount_read,
+            );
+            
+            if result == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            
+            Ok(amount_read as usize)
+            
+        }
+    }
+    
+}
+
+impl Drop for Body {
+    
+    fn drop(&mut self) {
+        unsafe {
+            
+            ffi::WinHttpCloseHandle(self.handle);
+            
+        }
+    }
+    
+}
+
+fn open(connection: &Connection, request: &Request, credentials: Option<(&str, &str)>) -> io::Result<Response> {
+    // -------------------- handle --------------------
+    
+    let handle = unsafe {
+        
+        let flags = if request.url.is_secure() {
+            ffi::WINHTTP_FLAG_SECURE
+        } else {
+            0
+        };
+        
+        let handle = ffi::WinHttpOpenRequest(
+            connection.handle,
+            chikuwa::WinString::from("GET").as_ptr(),
+            chikuwa::WinString::from(request.url.request_target().as_str()).as_ptr(),
+            ptr::null(),
+            ffi::WINHTTP_NO_REFERER,
+            ffi::WINHTTP_DEFAULT_ACCEPT_TYPES,
+            flags,
+        );
+        
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        
+        handle
+        
+    };
+    
+    // closes the handle on every early return
+    let body = Body { handle };
+    
+    // -------------------- timeouts --------------------
+    
+    if let Some(timeouts) = request.timeouts {
+        timeouts.apply(handle)?;
+    }
+    
+    // -------------------- proxy credentials --------------------
+    
+    if let Some((username, password)) = credentials {
+        for (option, value) in [(ffi::WINHTTP_OPTION_PROXY_USERNAME, username), (ffi::WINHTTP_OPTION_PROXY_PASSWORD, password)] {
+            unsafe {
+                
+                let value = chikuwa::WinString::from(value);
+                
+                // length of string options is expressed in characters, terminator excluded
+                #[allow(clippy::cast_possible_truncation)]
+                let length = (value.len() - 1) as c_ulong;
+                
+                let result = ffi::WinHttpSetOption(
+                    handle,
+                    option,
+                    value.as_ptr().cast_mut().cast::<c_void>(),
+                    length,
+                );
+                
+                if result == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                
+            }
+        }
+    }
+    
+    // -------------------- redirects and cookies --------------------
+    
+    // followed by the client to enforce its redirect policy, with cookies being handled by its own jar
+    
+    unsafe {
+        
+        let mut feature = ffi::WINHTTP_DISABLE_REDIRECTS | ffi::WINHTTP_DISABLE_COOKIES;
+        
+        #[allow(clippy::cast_possible_truncation)]
+        let bytes = mem::size_of::<c_ulong>() as c_ulong;
+        
+        let result = ffi::WinHttpSetOption(
+            handle,
+            ffi::WINHTTP_OPTION_DISABLE_FEATURE,
+            ptr::from_mut(&mut feature).cast::<c_void>(),
+            bytes,
+        );
+        
+        if result == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        
+    }
+    
+    // -------------------- send --------------------
+    
+    unsafe {
+        
+        let headers = (! request.headers.is_empty()).then(|| chikuwa::WinString::from(request.headers));
+        
+        // length can be -1 if the headers are null-terminated
+        let result = ffi::WinHttpSendRequest(
+            handle,
+            headers.as_ref().map_or(ffi::WINHTTP_NO_ADDITIONAL_HEADERS, |headers| headers.as_ptr()),
+            headers.as_ref().map_or(0, |_| c_ulong::MAX),
+            ffi::WINHTTP_NO_REQUEST_DATA,
+            0,
+            0,
+            0,
+        );
+        
+        if result == 0 {
+            return Err(network_error());
+        }
+        
+    }
+    
+    // -------------------- receive --------------------
+    
+    unsafe {
+        
+        let result = ffi::WinHttpReceiveResponse(
+            handle,
+            ptr::null_mut(),
+        );
+        
+        if result == 0 {
+            return Err(network_error());
+        }
+        
+    }
+    
+    // -------------------- status and headers --------------------
+    
+    let status = u16::try_from(query_number(handle, ffi::WINHTTP_QUERY_STATUS_CODE)).unwrap_or(0);
+    
+    // status line first, then one header per line
+    let headers = query_text(handle, ffi::WINHTTP_QUERY_RAW_HEADERS_CRLF)
+        .unwrap_or_default()
+        .split("\r\n")
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+        .collect();
+    
+    Ok(Response {
+        status,
+        headers,
+        body: Box::new(body),
+    })
+}
+
+// errors of WinHTTP are not known to the standard library, so those worth retrying are given a matching kind
+fn network_error() -> io::Error {
+    let error = io::Error::last_os_error();
+    
+    let kind = match error.raw_os_error() {
+        Some(ffi::ERROR_WINHTTP_TIMEOUT) => io::ErrorKind::TimedOut,
+        Some(ffi::ERROR_WINHTTP_CANNOT_CONNECT) => io::ErrorKind::ConnectionRefused,
+        Some(ffi::ERROR_WINHTTP_CONNECTION_ERROR) => io::ErrorKind::ConnectionReset,
+        _ => return error,
+    };
+    
+    io::Error::new(kind, error)
+}
+
+fn query_number(handle: ffi::HINTERNET, info: c_ulong) -> c_ulong {
+    unsafe {
+        
+        let mut number: c_ulong = 0;
+        
+        #[allow(clippy::cast_possible_truncation)]
+        let mut bytes = mem::size_of::<c_ulong>() as c_ulong;
+        
+        ffi::WinHttpQueryHeaders(
+            handle,
+            info | ffi::WINHTTP_QUERY_FLAG_NUMBER,
+            ffi::WINHTTP_HEADER_NAME_BY_INDEX,
+            ptr::from_mut(&mut number).cast::<c_void>(),
+            &mut bytes,
+            ffi::WINHTTP_NO_HEADER_INDEX,
+        );
+        
+        number
+        
+    }
+}
+
+fn query_text(handle: ffi::HINTERNET, info: c_ulong) -> Option<String> {
+    unsafe {
+        
+        // first call only retrieves the required size in bytes, terminator included
+        
+        let mut bytes: c_ulong = 0;
+        
+        ffi::WinHttpQueryHeaders(
+            handle,
+            info,
+            ffi::WINHTTP_HEADER_NAME_BY_INDEX,
+            ptr::null_mut(),
+            &mut bytes,
+            ffi::WINHTTP_NO_HEADER_INDEX,
+        );
+        
+        if bytes == 0 {
+            return None;
+        }
+        
+        let mut buffer: Vec<c_ushort> = vec![0; bytes as usize / mem::size_of::<c_ushort>()];
+        
+        let result = ffi::WinHttpQueryHeaders(
+            handle,
+            info,
+            ffi::WINHTTP_HEADER_NAME_BY_INDEX,
+            buffer.as_mut_ptr().cast::<c_void>(),
+            &mut bytes,
+            ffi::WINHTTP_NO_HEADER_INDEX,
+        );
+        
+        if result == 0 {
+            return None;
+        }
+        
+        buffer.truncate(bytes as usize / mem::size_of::<c_ushort>());
+        
+        String::from_utf16(&buffer).ok()
+        
+    }
+}
@@ -0,0 +1,30 @@
+use std::{ env, fs };
+
+use akari::{ Client, CookieJar, MockTransport, MockResponse };
+
+#[test]
+fn expires_before_epoch_removes_cookie() {
+    let path = chikuwa::EphemeralPath::builder()
+        .with_base(env::temp_dir())
+        .with_suffix("-akari-cookies.txt")
+        .build();
+    
+    fs::write(&path, "example.com\tFALSE\t/\tFALSE\t4102444800\tsession\tabc\n").unwrap();
+    
+    let cookie_jar = CookieJar::load(path.to_path_buf()).unwrap();
+    assert_eq!(cookie_jar.len(), 1);
+    
+    let transport = MockTransport::new()
+        .with_response("http://example.com/logout", MockResponse::new(200, "")
+            .with_header("Set-Cookie", "session=; Expires=Wed, 31 Dec 1969 23:59:59 GMT"));
+    
+    let mut client = Client::builder()
+        .with_transport(transport)
+        .with_cookie_jar(cookie_jar)
+        .build()
+        .unwrap();
+    
+    client.get("http://example.com/logout").unwrap();
+    
+    assert!(client.cookie_jar().unwrap().is_empty());
+}
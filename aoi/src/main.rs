@@ -50,6 +50,10 @@ fn process() -> Result<(), Box<dyn Error>> {
     println!();
     println!("Loading configuration...");
     
+    rin::register(rin::Schema::new()
+        .with_required(b"address", rin::Kind::Text)
+        .with_required(b"name", rin::Kind::Text))?;
+    
     let address = rin::get(b"address")?;
     let name = rin::get(b"name")?;
     
@@ -26,6 +26,8 @@ pub enum ContentType {
     Icon,
     Css,
     Javascript,
+    Csv,
+    Json,
 }
 
 pub enum CacheControl {
@@ -58,6 +60,8 @@ impl ContentType {
             Self::Icon => b"Content-Type: image/x-icon\r\n",
             Self::Css => b"Content-Type: text/css; charset=utf-8\r\n",
             Self::Javascript => b"Content-Type: text/javascript; charset=utf-8\r\n",
+            Self::Csv => b"Content-Type: text/csv; charset=utf-8\r\n",
+            Self::Json => b"Content-Type: application/json; charset=utf-8\r\n",
         }
     }
     
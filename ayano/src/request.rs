@@ -102,7 +102,7 @@ impl Request {
     
     pub fn start_response(&mut self, status: StatusCode, content: ContentType, cache: CacheControl) -> io::Result<Response> {
         let stream = self.stream.take()
-            .ok_or(io::Error::new(io::ErrorKind::Other, "Response already sent"))?;
+            .ok_or(io::Error::other("Response already sent"))?;
         
         Response::new(stream, status, content, cache)
     }
@@ -0,0 +1,21 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+// a change that could not be reapplied on top of the ones committed by another process
+#[derive(Debug)]
+pub struct Conflict {
+    pub tag: Vec<u8>,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for Conflict {
+    
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Conflicting change to tag '{}' committed by another process: {}", String::from_utf8_lossy(&self.tag), self.reason)
+    }
+    
+}
+
+impl Error for Conflict {}
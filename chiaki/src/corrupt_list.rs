@@ -0,0 +1,20 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+#[derive(Debug)]
+pub struct CorruptList {
+    pub offset: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for CorruptList {
+    
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Corrupt list at byte offset {}: {}", self.offset, self.reason)
+    }
+    
+}
+
+impl Error for CorruptList {}
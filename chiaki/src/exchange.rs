@@ -0,0 +1,618 @@
+use std::{
+    error::Error,
+    io::{ Read, Write },
+    mem,
+    str::{ self, FromStr },
+};
+
+use super::{ ListEntry, Metadata, Status };
+
+const COLUMNS: [&str; 8] = ["tag", "value", "total", "status", "score", "notes", "added", "updated"];
+
+// an array of objects, deeper documents being rejected before they can exhaust the stack
+const JSON_MAX_DEPTH: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Csv,
+    Json,
+    Text,
+}
+
+// how imported entries are combined with the ones already in the list
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MergePolicy {
+    // the list ends up holding the imported entries only
+    Replace,
+    // entries already in the list are left untouched
+    Skip,
+    // entries already in the list take the imported value and metadata
+    Overwrite,
+}
+
+pub struct Record {
+    pub tag: Vec<u8>,
+    pub value: u64,
+    // none when the format does not carry it, leaving the current one in place
+    pub metadata: Option<Metadata>,
+}
+
+struct Row {
+    line: usize,
+    cells: Vec<String>,
+}
+
+enum Json {
+    Null,
+    Bool,
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct JsonParser<'s> {
+    content: &'s [u8],
+    offset: usize,
+    depth: usize,
+}
+
+impl Format {
+    
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Text => "txt",
+        }
+    }
+    
+}
+
+impl FromStr for Format {
+    
+    type Err = Box<dyn Error>;
+    
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "text" | "txt" => Ok(Self::Text),
+            _ => Err(format!("Unknown list format '{}'", input).into()),
+        }
+    }
+    
+}
+
+impl FromStr for MergePolicy {
+    
+    type Err = Box<dyn Error>;
+    
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "replace" => Ok(Self::Replace),
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            _ => Err(format!("Unknown merge policy '{}'", input).into()),
+        }
+    }
+    
+}
+
+
+// -------------------- export --------------------
+
+
+// tags and notes are not required to be valid UTF-8, so invalid sequences are replaced
+pub fn export<'c, W: Write>(entries: impl Iterator<Item = ListEntry<'c>>, mut writer: W, format: Format) -> Result<(), Box<dyn Error>> {
+    match format {
+        
+        Format::Csv => {
+            writeln!(writer, "{}", COLUMNS.join(","))?;
+            for entry in entries {
+                let metadata = entry.metadata();
+                let cells = [
+                    csv_escape(&String::from_utf8_lossy(entry.tag)),
+                    entry.value.to_string(),
+                    optional(metadata.total),
+                    metadata.status.map(Status::name).unwrap_or_default().to_owned(),
+                    optional(metadata.score),
+                    csv_escape(&String::from_utf8_lossy(metadata.notes.as_deref().unwrap_or_default())),
+                    optional(entry.added()),
+                    optional(entry.updated()),
+                ];
+                writeln!(writer, "{}", cells.join(","))?;
+            }
+        },
+        
+        Format::Json => {
+            writer.write_all(b"[")?;
+            for (position, entry) in entries.enumerate() {
+                let metadata = entry.metadata();
+                
+                // missing fields are omitted rather than written as null
+                let members = [
+                    Some(json_escape(&String::from_utf8_lossy(entry.tag))),
+                    Some(entry.value.to_string()),
+                    metadata.total.map(|total| total.to_string()),
+                    metadata.status.map(|status| json_escape(status.name())),
+                    metadata.score.map(|score| score.to_string()),
+                    metadata.notes.map(|notes| json_escape(&String::from_utf8_lossy(&notes))),
+                    entry.added().map(|added| added.to_string()),
+                    entry.updated().map(|updated| updated.to_string()),
+                ];
+                
+                let members: Vec<String> = COLUMNS.iter()
+                    .zip(members)
+                    .filter_map(|(name, member)| Some(format!("\"{}\": {}", name, member?)))
+                    .collect();
+                
+                write!(writer, "{}\n  {{{}}}", if position == 0 { "" } else { "," }, members.join(", "))?;
+            }
+            writer.write_all(b"\n]\n")?;
+        },
+        
+        // value first, so that the rest of the line is the tag as is
+        Format::Text => {
+            for entry in entries {
+                if entry.tag.contains(&b'\n') || entry.tag.contains(&b'\r') {
+                    return Err(format!("Tag '{}' contains a line break and cannot be exported as text", String::from_utf8_lossy(entry.tag)).into());
+                }
+                write!(writer, "{} ", entry.value)?;
+                writer.write_all(entry.tag)?;
+                writer.write_all(b"\n")?;
+            }
+        },
+        
+    }
+    
+    writer.flush()?;
+    
+    Ok(())
+}
+
+fn optional(number: Option<u64>) -> String {
+    number.map(|number| number.to_string()).unwrap_or_default()
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_owned()
+    }
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    
+    escaped.push('"');
+    
+    for current in text.chars() {
+        match current {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            current if current.is_control() => escaped.push_str(&format!("\\u{:04x}", current as u32)),
+            current => escaped.push(current),
+        }
+    }
+    
+    escaped.push('"');
+    
+    escaped
+}
+
+
+// -------------------- import --------------------
+
+
+// timestamps are not imported, being set as for any other insertion or update
+pub fn import<R: Read>(mut reader: R, format: Format) -> Result<Vec<Record>, Box<dyn Error>> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    
+    match format {
+        Format::Csv => import_csv(&content),
+        Format::Json => import_json(&content),
+        Format::Text => import_text(&content),
+    }
+}
+
+fn import_csv(content: &str) -> Result<Vec<Record>, Box<dyn Error>> {
+    let rows = parse_csv(content)?;
+    
+    let mut rows = rows.into_iter()
+        .filter(|row| ! (row.cells.len() == 1 && row.cells[0].is_empty()));
+    
+    // columns are matched by name, in any order, with unknown ones being ignored
+    let header = rows.next().ok_or("Missing CSV header")?.cells;
+    
+    let column = |name: &str| header.iter().position(|current| current.trim().eq_ignore_ascii_case(name));
+    
+    let tag_column = column("tag").ok_or("Missing CSV column 'tag'")?;
+    let value_column = column("value").ok_or("Missing CSV column 'value'")?;
+    let total_column = column("total");
+    let status_column = column("status");
+    let score_column = column("score");
+    let notes_column = column("notes");
+    
+    rows.map(|Row { line, cells }| {
+        
+        let invalid = |reason: &str| format!("Invalid CSV at line {}: {}", line, reason);
+        
+        let cell = |column: Option<usize>| column
+            .and_then(|column| cells.get(column))
+            .map(String::as_str)
+            .filter(|cell| ! cell.is_empty());
+        
+        let number = |column: Option<usize>, name: &str| cell(column)
+            .map(|cell| cell.trim().parse::<u64>().map_err(|_| invalid(&format!("invalid {}", name))))
+            .transpose();
+        
+        let tag = cell(Some(tag_column)).ok_or_else(|| invalid("missing tag"))?;
+        let value = number(Some(value_column), "value")?.ok_or_else(|| invalid("missing value"))?;
+        
+        let status = cell(status_column)
+            .map(|status| Status::from_name(status.trim()).ok_or_else(|| invalid("invalid status")))
+            .transpose()?;
+        
+        Ok(Record {
+            tag: tag.as_bytes().to_vec(),
+            value,
+            metadata: Some(Metadata {
+                total: number(total_column, "total")?,
+                status,
+                score: number(score_column, "score")?,
+                notes: cell(notes_column).map(|notes| notes.as_bytes().to_vec()),
+            }),
+        })
+        
+    })
+    .collect::<Result<_, String>>()
+    .map_err(Into::into)
+}
+
+// https://www.rfc-editor.org/rfc/rfc4180, accepting bare line feeds as well
+fn parse_csv(content: &str) -> Result<Vec<Row>, Box<dyn Error>> {
+    let mut rows = Vec::new();
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    
+    let mut line = 1;
+    let mut start = 1;
+    let mut quoted = false;
+    
+    let mut chars = content.chars().peekable();
+    
+    while let Some(current) = chars.next() {
+        
+        if current == '\n' {
+            line += 1;
+        }
+        
+        if quoted {
+            match current {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                },
+                '"' => quoted = false,
+                current => cell.push(current),
+            }
+            continue;
+        }
+        
+        match current {
+            '"' if cell.is_empty() => quoted = true,
+            ',' => cells.push(mem::take(&mut cell)),
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                cells.push(mem::take(&mut cell));
+                rows.push(Row { line: start, cells: mem::take(&mut cells) });
+                start = line;
+            },
+            current => cell.push(current),
+        }
+        
+    }
+    
+    if quoted {
+        return Err(format!("Invalid CSV at line {}: unterminated quoted cell", start).into());
+    }
+    
+    if ! cell.is_empty() || ! cells.is_empty() {
+        cells.push(cell);
+        rows.push(Row { line: start, cells });
+    }
+    
+    Ok(rows)
+}
+
+fn import_json(content: &str) -> Result<Vec<Record>, Box<dyn Error>> {
+    let mut parser = JsonParser {
+        content: content.as_bytes(),
+        offset: 0,
+        depth: 0,
+    };
+    
+    let Json::Array(items) = parser.document()? else {
+        return Err("Invalid JSON: expected an array of entries".into());
+    };
+    
+    items.into_iter()
+        .enumerate()
+        .map(|(position, item)| {
+            
+            let invalid = |reason: &str| format!("Invalid JSON entry at position {}: {}", position, reason);
+            
+            let Json::Object(members) = item else {
+                return Err(invalid("expected an object"));
+            };
+            
+            let mut tag = None;
+            let mut value = None;
+            let mut metadata = Metadata::default();
+            
+            // unknown members, timestamps included, are ignored
+            for (name, member) in members {
+                match (name.as_str(), member) {
+                    (_, Json::Null) => (),
+                    ("tag", Json::String(current)) => tag = Some(current),
+                    ("value", Json::Number(current)) => value = Some(current),
+                    ("total", Json::Number(current)) => metadata.total = Some(current),
+                    ("score", Json::Number(current)) => metadata.score = Some(current),
+                    ("notes", Json::String(current)) => metadata.notes = Some(current.into_bytes()),
+                    ("status", Json::String(current)) => metadata.status = Some(Status::from_name(&current).ok_or_else(|| invalid("invalid status"))?),
+                    ("tag" | "value" | "total" | "score" | "notes" | "status", _) => return Err(invalid(&format!("invalid {}", name))),
+                    _ => (),
+                }
+            }
+            
+            Ok(Record {
+                tag: tag.filter(|tag| ! tag.is_empty()).ok_or_else(|| invalid("missing tag"))?.into_bytes(),
+                value: value.ok_or_else(|| invalid("missing value"))?,
+                metadata: Some(metadata),
+            })
+            
+        })
+        .collect::<Result<_, String>>()
+        .map_err(Into::into)
+}
+
+fn import_text(content: &str) -> Result<Vec<Record>, Box<dyn Error>> {
+    content.lines()
+        .enumerate()
+        .filter(|(_, line)| ! line.trim().is_empty())
+        .map(|(index, line)| {
+            
+            let (value, tag) = line.split_once(' ')
+                .filter(|(_, tag)| ! tag.is_empty())
+                .ok_or_else(|| format!("Invalid text at line {}: expected a value followed by a tag", index + 1))?;
+            
+            let value = value.parse()
+                .map_err(|_| format!("Invalid text at line {}: invalid value", index + 1))?;
+            
+            Ok(Record {
+                tag: tag.as_bytes().to_vec(),
+                value,
+                metadata: None,
+            })
+            
+        })
+        .collect()
+}
+
+// https://www.rfc-editor.org/rfc/rfc8259, limited to what lists can hold: numbers must be unsigned integers
+impl JsonParser<'_> {
+    
+    fn document(&mut self) -> Result<Json, Box<dyn Error>> {
+        let value = self.value()?;
+        
+        self.whitespace();
+        
+        if self.offset < self.content.len() {
+            return Err(self.error("trailing characters"));
+        }
+        
+        Ok(value)
+    }
+    
+    fn value(&mut self) -> Result<Json, Box<dyn Error>> {
+        self.whitespace();
+        
+        match self.content.get(self.offset) {
+            
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal(b"true", Json::Bool),
+            Some(b'f') => self.literal(b"false", Json::Bool),
+            Some(b'n') => self.literal(b"null", Json::Null),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end")),
+            
+        }
+    }
+    
+    fn nested(&mut self, container: fn(&mut Self) -> Result<Json, Box<dyn Error>>) -> Result<Json, Box<dyn Error>> {
+        if self.depth == JSON_MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        
+        self.depth += 1;
+        let value = container(self)?;
+        self.depth -= 1;
+        
+        Ok(value)
+    }
+    
+    fn object(&mut self) -> Result<Json, Box<dyn Error>> {
+        self.offset += 1;
+        
+        let mut members = Vec::new();
+        
+        if self.consume(b'}') {
+            return Ok(Json::Object(members));
+        }
+        
+        loop {
+            self.whitespace();
+            let name = self.string()?;
+            if ! self.consume(b':') {
+                return Err(self.error("expected ':'"));
+            }
+            members.push((name, self.value()?));
+            if self.consume(b'}') {
+                return Ok(Json::Object(members));
+            }
+            if ! self.consume(b',') {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+    
+    fn array(&mut self) -> Result<Json, Box<dyn Error>> {
+        self.offset += 1;
+        
+        let mut items = Vec::new();
+        
+        if self.consume(b']') {
+            return Ok(Json::Array(items));
+        }
+        
+        loop {
+            items.push(self.value()?);
+            if self.consume(b']') {
+                return Ok(Json::Array(items));
+            }
+            if ! self.consume(b',') {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+    
+    fn number(&mut self) -> Result<Json, Box<dyn Error>> {
+        let start = self.offset;
+        
+        while self.content.get(self.offset).is_some_and(u8::is_ascii_digit) {
+            self.offset += 1;
+        }
+        
+        if matches!(self.content.get(self.offset), Some(b'.' | b'e' | b'E')) {
+            return Err(self.error("only unsigned integers are supported"));
+        }
+        
+        String::from_utf8_lossy(&self.content[start..self.offset]).parse()
+            .map(Json::Number)
+            .map_err(|_| self.error("number out of range"))
+    }
+    
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        if ! self.consume(b'"') {
+            return Err(self.error("expected string"));
+        }
+        
+        let mut string = Vec::new();
+        
+        loop {
+            
+            let current = *self.content.get(self.offset).ok_or_else(|| self.error("unterminated string"))?;
+            self.offset += 1;
+            
+            match current {
+                
+                b'"' => break,
+                
+                b'\\' => {
+                    let escape = *self.content.get(self.offset).ok_or_else(|| self.error("unterminated string"))?;
+                    self.offset += 1;
+                    match escape {
+                        b'"' | b'\\' | b'/' => string.push(escape),
+                        b'b' => string.push(0x08),
+                        b'f' => string.push(0x0c),
+                        b'n' => string.push(b'\n'),
+                        b'r' => string.push(b'\r'),
+                        b't' => string.push(b'\t'),
+                        b'u' => {
+                            let decoded = self.unicode_escape()?;
+                            string.extend_from_slice(decoded.encode_utf8(&mut [0; 4]).as_bytes());
+                        },
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                },
+                
+                current if current < 0x20 => return Err(self.error("unescaped control character")),
+                
+                current => string.push(current),
+                
+            }
+            
+        }
+        
+        // the content comes from a string, so anything between quotes is valid as well
+        String::from_utf8(string).map_err(|_| self.error("invalid string"))
+    }
+    
+    fn unicode_escape(&mut self) -> Result<char, Box<dyn Error>> {
+        let high = self.hex()?;
+        
+        // characters outside the basic multilingual plane are encoded as surrogate pairs
+        if (0xD800..0xDC00).contains(&high) && self.content[self.offset..].starts_with(b"\\u") {
+            self.offset += 2;
+            let low = self.hex()?;
+            if (0xDC00..0xE000).contains(&low) {
+                return char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                    .ok_or_else(|| self.error("invalid unicode escape"));
+            }
+        }
+        
+        char::from_u32(high).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+    
+    fn hex(&mut self) -> Result<u32, Box<dyn Error>> {
+        let digits = self.content.get(self.offset..self.offset + 4)
+            .and_then(|digits| str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        
+        self.offset += 4;
+        
+        Ok(digits)
+    }
+    
+    fn literal(&mut self, literal: &[u8], value: Json) -> Result<Json, Box<dyn Error>> {
+        if ! self.content[self.offset..].starts_with(literal) {
+            return Err(self.error("unexpected character"));
+        }
+        
+        self.offset += literal.len();
+        
+        Ok(value)
+    }
+    
+    fn consume(&mut self, expected: u8) -> bool {
+        self.whitespace();
+        
+        if self.content.get(self.offset) == Some(&expected) {
+            self.offset += 1;
+            return true;
+        }
+        
+        false
+    }
+    
+    fn whitespace(&mut self) {
+        while matches!(self.content.get(self.offset), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.offset += 1;
+        }
+    }
+    
+    fn error(&self, reason: &str) -> Box<dyn Error> {
+        format!("Invalid JSON at byte offset {}: {}", self.offset, reason).into()
+    }
+    
+}
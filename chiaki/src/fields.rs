@@ -0,0 +1,207 @@
+use std::{
+    mem,
+    time::{ SystemTime, UNIX_EPOCH },
+};
+
+// every field is an id (u16), a kind (u8), a length (u32) and the data, all little-endian
+pub const FIELD_HEADER_SIZE: usize = mem::size_of::<u16>() + mem::size_of::<u8>() + mem::size_of::<u32>();
+
+const KIND_NUMBER: u8 = 0;
+const KIND_BYTES: u8 = 1;
+
+const FIELD_TOTAL: u16 = 1;
+const FIELD_STATUS: u16 = 2;
+const FIELD_SCORE: u16 = 3;
+const FIELD_NOTES: u16 = 4;
+const FIELD_ADDED: u16 = 5;
+const FIELD_UPDATED: u16 = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Watching,
+    Completed,
+    Dropped,
+    Planned,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Metadata {
+    pub total: Option<u64>,
+    pub status: Option<Status>,
+    pub score: Option<u64>,
+    pub notes: Option<Vec<u8>>,
+}
+
+pub struct FieldsIter<'c> {
+    rest: &'c [u8],
+}
+
+impl Status {
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Watching => "watching",
+            Self::Completed => "completed",
+            Self::Dropped => "dropped",
+            Self::Planned => "planned",
+        }
+    }
+    
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Watching, Self::Completed, Self::Dropped, Self::Planned].into_iter()
+            .find(|status| status.name().eq_ignore_ascii_case(name))
+    }
+    
+    fn code(self) -> u64 {
+        match self {
+            Self::Watching => 1,
+            Self::Completed => 2,
+            Self::Dropped => 3,
+            Self::Planned => 4,
+        }
+    }
+    
+    fn from_code(code: u64) -> Option<Self> {
+        match code {
+            1 => Some(Self::Watching),
+            2 => Some(Self::Completed),
+            3 => Some(Self::Dropped),
+            4 => Some(Self::Planned),
+            _ => None,
+        }
+    }
+    
+}
+
+impl<'c> Iterator for FieldsIter<'c> {
+    
+    type Item = (u16, u8, &'c [u8]);
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        // fields are validated on load, so anything unexpected can only be the end
+        let header = self.rest.get(..FIELD_HEADER_SIZE)?;
+        
+        let id = u16::from_le_bytes([header[0], header[1]]);
+        let kind = header[2];
+        let length = usize::try_from(u32::from_le_bytes([header[3], header[4], header[5], header[6]])).ok()?;
+        
+        let data = self.rest.get(FIELD_HEADER_SIZE..FIELD_HEADER_SIZE + length)?;
+        self.rest = &self.rest[FIELD_HEADER_SIZE + length..];
+        
+        Some((id, kind, data))
+    }
+    
+}
+
+pub fn iter(fields: &[u8]) -> FieldsIter<'_> {
+    FieldsIter { rest: fields }
+}
+
+fn number(fields: &[u8], id: u16) -> Option<u64> {
+    iter(fields)
+        .find(|&(current, kind, _)| current == id && kind == KIND_NUMBER)
+        .and_then(|(_, _, data)| data.first_chunk::<8>().copied())
+        .map(u64::from_le_bytes)
+}
+
+fn bytes(fields: &[u8], id: u16) -> Option<&[u8]> {
+    iter(fields)
+        .find(|&(current, kind, _)| current == id && kind == KIND_BYTES)
+        .map(|(_, _, data)| data)
+}
+
+pub fn metadata(fields: &[u8]) -> Metadata {
+    Metadata {
+        total: total(fields),
+        status: status(fields),
+        score: score(fields),
+        notes: notes(fields).map(<[u8]>::to_vec),
+    }
+}
+
+pub fn total(fields: &[u8]) -> Option<u64> {
+    number(fields, FIELD_TOTAL)
+}
+
+pub fn status(fields: &[u8]) -> Option<Status> {
+    number(fields, FIELD_STATUS).and_then(Status::from_code)
+}
+
+pub fn score(fields: &[u8]) -> Option<u64> {
+    number(fields, FIELD_SCORE)
+}
+
+pub fn notes(fields: &[u8]) -> Option<&[u8]> {
+    bytes(fields, FIELD_NOTES)
+}
+
+pub fn added(fields: &[u8]) -> Option<u64> {
+    number(fields, FIELD_ADDED)
+}
+
+pub fn updated(fields: &[u8]) -> Option<u64> {
+    number(fields, FIELD_UPDATED)
+}
+
+// fields of a new entry, which might have been added before
+pub fn fresh(added: Option<u64>) -> Vec<u8> {
+    let mut fields = Vec::new();
+    
+    if let Some(added) = added {
+        push(&mut fields, FIELD_ADDED, KIND_NUMBER, &added.to_le_bytes());
+    }
+    
+    merge(&fields, None)
+}
+
+// rewrites the known fields, leaving the unknown ones untouched and refreshing the timestamps
+pub fn merge(fields: &[u8], metadata: Option<&Metadata>) -> Vec<u8> {
+    let now = now();
+    let added = added(fields).unwrap_or(now);
+    
+    let metadata = metadata.cloned().unwrap_or_else(|| self::metadata(fields));
+    
+    let mut merged = Vec::with_capacity(fields.len() + FIELD_HEADER_SIZE * 6);
+    
+    for (id, kind, data) in iter(fields).filter(|&(id, _, _)| ! (FIELD_TOTAL..=FIELD_UPDATED).contains(&id)) {
+        push(&mut merged, id, kind, data);
+    }
+    
+    if let Some(total) = metadata.total {
+        push(&mut merged, FIELD_TOTAL, KIND_NUMBER, &total.to_le_bytes());
+    }
+    
+    if let Some(status) = metadata.status {
+        push(&mut merged, FIELD_STATUS, KIND_NUMBER, &status.code().to_le_bytes());
+    }
+    
+    if let Some(score) = metadata.score {
+        push(&mut merged, FIELD_SCORE, KIND_NUMBER, &score.to_le_bytes());
+    }
+    
+    if let Some(notes) = &metadata.notes {
+        push(&mut merged, FIELD_NOTES, KIND_BYTES, notes);
+    }
+    
+    push(&mut merged, FIELD_ADDED, KIND_NUMBER, &added.to_le_bytes());
+    push(&mut merged, FIELD_UPDATED, KIND_NUMBER, &now.to_le_bytes());
+    
+    merged
+}
+
+fn push(fields: &mut Vec<u8>, id: u16, kind: u8, data: &[u8]) {
+    // anything beyond the maximum length is dropped
+    let length = u32::try_from(data.len()).unwrap_or(u32::MAX);
+    
+    fields.extend_from_slice(&id.to_le_bytes());
+    fields.push(kind);
+    fields.extend_from_slice(&length.to_le_bytes());
+    fields.extend_from_slice(&data[..length as usize]);
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
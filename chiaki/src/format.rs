@@ -0,0 +1,158 @@
+use std::mem;
+
+use super::{ CorruptList, fields };
+
+// -------------------- header --------------------
+
+// magic (4 bytes), version (u16), reserved (u16), generation (u64), crc-32 of the records (u32), all little-endian
+const MAGIC: [u8; 4] = *b"CHKI";
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 20;
+
+const MEM_SIZE: usize = mem::size_of::<u64>();
+
+pub struct Decoded {
+    pub records: Vec<u8>,
+    pub generation: u64,
+    pub migrated: bool,
+}
+
+pub fn encode(records: &[u8], generation: u64) -> Vec<u8> {
+    let mut content = Vec::with_capacity(HEADER_SIZE + records.len());
+    
+    content.extend_from_slice(&MAGIC);
+    content.extend_from_slice(&VERSION.to_le_bytes());
+    content.extend_from_slice(&0_u16.to_le_bytes());
+    content.extend_from_slice(&generation.to_le_bytes());
+    content.extend_from_slice(&chikuwa::Crc32::checksum(records).to_le_bytes());
+    content.extend_from_slice(records);
+    
+    content
+}
+
+pub fn decode(mut content: Vec<u8>) -> Result<Decoded, CorruptList> {
+    // files written before the header was introduced are a bare sequence of records without fields
+    if ! content.starts_with(&MAGIC) {
+        validate(&content, 0, false)?;
+        return Ok(Decoded {
+            records: upgrade(&content),
+            generation: 0,
+            migrated: true,
+        });
+    }
+    
+    let version = content.get(4..6)
+        .map(|version| u16::from_le_bytes([version[0], version[1]]))
+        .ok_or(CorruptList { offset: content.len(), reason: "truncated header" })?;
+    
+    if version != VERSION {
+        return Err(CorruptList { offset: 4, reason: "unsupported format version" });
+    }
+    
+    let header = content.get(..HEADER_SIZE)
+        .ok_or(CorruptList { offset: content.len(), reason: "truncated header" })?;
+    
+    let generation = u64::from_le_bytes([header[8], header[9], header[10], header[11], header[12], header[13], header[14], header[15]]);
+    let checksum = u32::from_le_bytes([header[16], header[17], header[18], header[19]]);
+    
+    validate(&content[HEADER_SIZE..], HEADER_SIZE, true)?;
+    
+    if chikuwa::Crc32::checksum(&content[HEADER_SIZE..]) != checksum {
+        return Err(CorruptList { offset: 16, reason: "checksum mismatch" });
+    }
+    
+    content.drain(..HEADER_SIZE);
+    
+    Ok(Decoded {
+        records: content,
+        generation,
+        migrated: false,
+    })
+}
+
+
+// -------------------- records --------------------
+
+
+// tag length (u64), tag, value (u64), then fields length (u64) and fields except in headerless files, all little-endian
+fn validate(records: &[u8], base: usize, with_fields: bool) -> Result<(), CorruptList> {
+    let corrupt = |offset: usize, reason| CorruptList { offset: base + offset, reason };
+    
+    let mut offset = 0;
+    
+    while offset < records.len() {
+        
+        let start = offset;
+        
+        // to prevent data loss, tags too large for the target platform are not skipped
+        let tag_size = read_size(records, offset)
+            .ok_or(corrupt(start, "truncated tag length"))?
+            .ok_or(corrupt(start, "tag length too large for the target platform"))?;
+        
+        offset += MEM_SIZE;
+        
+        offset = offset.checked_add(tag_size)
+            .filter(|&end| end <= records.len())
+            .ok_or(corrupt(start, "truncated tag"))?;
+        
+        if records.len() - offset < MEM_SIZE {
+            return Err(corrupt(offset, "truncated value"));
+        }
+        
+        offset += MEM_SIZE;
+        
+        if ! with_fields {
+            continue;
+        }
+        
+        let fields_start = offset;
+        
+        let fields_size = read_size(records, offset)
+            .ok_or(corrupt(fields_start, "truncated fields length"))?
+            .ok_or(corrupt(fields_start, "fields length too large for the target platform"))?;
+        
+        offset += MEM_SIZE;
+        
+        let end = offset.checked_add(fields_size)
+            .filter(|&end| end <= records.len())
+            .ok_or(corrupt(fields_start, "truncated fields"))?;
+        
+        while offset < end {
+            
+            let header = records.get(offset..offset + fields::FIELD_HEADER_SIZE)
+                .filter(|_| offset + fields::FIELD_HEADER_SIZE <= end)
+                .ok_or(corrupt(offset, "truncated field header"))?;
+            
+            let length = u32::from_le_bytes([header[3], header[4], header[5], header[6]]) as usize;
+            
+            offset = (offset + fields::FIELD_HEADER_SIZE).checked_add(length)
+                .filter(|&field_end| field_end <= end)
+                .ok_or(corrupt(offset, "truncated field"))?;
+            
+        }
+        
+    }
+    
+    Ok(())
+}
+
+// every record gains an empty set of fields
+fn upgrade(records: &[u8]) -> Vec<u8> {
+    let mut upgraded = Vec::with_capacity(records.len() + records.len() / 2);
+    let mut offset = 0;
+    
+    while let Some(Some(tag_size)) = read_size(records, offset) {
+        let end = offset + MEM_SIZE + tag_size + MEM_SIZE;
+        upgraded.extend_from_slice(&records[offset..end]);
+        upgraded.extend_from_slice(&0_u64.to_le_bytes());
+        offset = end;
+    }
+    
+    upgraded
+}
+
+// none if missing, some none if it cannot be represented on the target platform
+fn read_size(records: &[u8], offset: usize) -> Option<Option<usize>> {
+    let bytes = records.get(offset..)?.first_chunk::<MEM_SIZE>()?;
+    Some(usize::try_from(u64::from_le_bytes(*bytes)).ok())
+}
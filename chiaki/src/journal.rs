@@ -0,0 +1,271 @@
+use std::{
+    error::Error,
+    fs::{ self, OpenOptions },
+    io::{ self, Write },
+    mem,
+    path::{ Path, PathBuf },
+    time::{ SystemTime, UNIX_EPOCH },
+};
+
+use super::{ Metadata, Status, fields, persist };
+
+// once exceeded, the oldest changes are dropped until the journal is back to half of it
+const SIZE_LIMIT: usize = 1024 * 1024;
+
+const MEM_SIZE: usize = mem::size_of::<u64>();
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+    Rename,
+}
+
+#[derive(Clone, Debug)]
+pub struct Change {
+    pub id: u64,
+    // seconds since the unix epoch
+    pub timestamp: u64,
+    // id of the change being undone, if any
+    pub reverts: Option<u64>,
+    pub tag: Vec<u8>,
+    // tag of the entry before being renamed, if the change is a rename
+    pub renamed_from: Option<Vec<u8>>,
+    pub before: Option<Snapshot>,
+    pub after: Option<Snapshot>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Snapshot {
+    pub value: u64,
+    pub(crate) fields: Vec<u8>,
+}
+
+impl Change {
+    
+    pub fn kind(&self) -> ChangeKind {
+        match (&self.before, &self.after) {
+            _ if self.renamed_from.is_some() => ChangeKind::Rename,
+            (None, _) => ChangeKind::Insert,
+            (Some(_), Some(_)) => ChangeKind::Update,
+            (Some(_), None) => ChangeKind::Delete,
+        }
+    }
+    
+}
+
+impl Snapshot {
+    
+    pub fn metadata(&self) -> Metadata {
+        fields::metadata(&self.fields)
+    }
+    
+    pub fn status(&self) -> Option<Status> {
+        fields::status(&self.fields)
+    }
+    
+    pub fn added(&self) -> Option<u64> {
+        fields::added(&self.fields)
+    }
+    
+    pub fn updated(&self) -> Option<u64> {
+        fields::updated(&self.fields)
+    }
+    
+}
+
+
+// -------------------- journal --------------------
+
+
+pub fn path(list_path: &Path) -> PathBuf {
+    list_path.with_extension("ck.journal")
+}
+
+// oldest first, a missing journal being an empty one
+pub fn read(list_path: &Path) -> Result<Vec<Change>, Box<dyn Error>> {
+    let (changes, _) = decode(&read_raw(list_path)?);
+    Ok(changes)
+}
+
+// expects the lock of the list to be held
+pub fn append(list_path: &Path, mut changes: Vec<Change>) -> Result<(), Box<dyn Error>> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    
+    let path = path(list_path);
+    
+    let content = read_raw(list_path)?;
+    let (mut history, valid) = decode(&content);
+    
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    
+    let mut id = history.last().map_or(0, |change| change.id);
+    
+    for change in &mut changes {
+        id += 1;
+        change.id = id;
+        change.timestamp = timestamp;
+    }
+    
+    let mut appended = Vec::new();
+    
+    for change in &changes {
+        encode(change, &mut appended);
+    }
+    
+    let size = content.len() + appended.len();
+    
+    // a record left truncated by an interrupted append has to be dropped first, which requires a rewrite
+    if valid == content.len() && size <= SIZE_LIMIT {
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        file.write_all(&appended)?;
+        file.sync_all()?;
+        return Ok(());
+    }
+    
+    // -------------------- rewrite and compaction --------------------
+    
+    history.extend(changes);
+    
+    let budget = if size > SIZE_LIMIT { SIZE_LIMIT / 2 } else { SIZE_LIMIT };
+    
+    let kept: Vec<Vec<u8>> = history.iter()
+        .rev()
+        .map(|change| {
+            let mut encoded = Vec::new();
+            encode(change, &mut encoded);
+            encoded
+        })
+        .scan(0, |size, encoded| {
+            *size += encoded.len();
+            (*size <= budget).then_some(encoded)
+        })
+        .collect();
+    
+    let compacted: Vec<u8> = kept.into_iter()
+        .rev()
+        .flatten()
+        .collect();
+    
+    persist::replace(&path, &compacted)
+}
+
+
+// -------------------- records --------------------
+
+
+// id, timestamp, reverted id (zero for none), tag length and tag, then before and after snapshots
+// every snapshot is a presence flag (u8) optionally followed by value, fields length and fields, all little-endian
+// renames flag their before snapshot with 2 instead of 1, followed by the previous tag length and tag
+fn encode(change: &Change, buffer: &mut Vec<u8>) {
+    buffer.extend_from_slice(&change.id.to_le_bytes());
+    buffer.extend_from_slice(&change.timestamp.to_le_bytes());
+    buffer.extend_from_slice(&change.reverts.unwrap_or(0).to_le_bytes());
+    buffer.extend_from_slice(&(change.tag.len() as u64).to_le_bytes());
+    buffer.extend_from_slice(&change.tag);
+    
+    for (snapshot, renamed_from) in [(&change.before, &change.renamed_from), (&change.after, &None)] {
+        match (snapshot, renamed_from) {
+            (None, _) => buffer.push(0),
+            (Some(snapshot), renamed_from) => {
+                buffer.push(if renamed_from.is_some() { 2 } else { 1 });
+                buffer.extend_from_slice(&snapshot.value.to_le_bytes());
+                buffer.extend_from_slice(&(snapshot.fields.len() as u64).to_le_bytes());
+                buffer.extend_from_slice(&snapshot.fields);
+                if let Some(renamed_from) = renamed_from {
+                    buffer.extend_from_slice(&(renamed_from.len() as u64).to_le_bytes());
+                    buffer.extend_from_slice(renamed_from);
+                }
+            },
+        }
+    }
+}
+
+fn read_raw(list_path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    match fs::read(path(list_path)) {
+        Ok(content) => Ok(content),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+// a record left truncated by an interrupted append is where the journal ends, returns the length of the valid part
+fn decode(content: &[u8]) -> (Vec<Change>, usize) {
+    let mut changes = Vec::new();
+    let mut rest = content;
+    
+    loop {
+        
+        // only moved past complete records
+        let mut working = rest;
+        
+        let Some(change) = decode_change(&mut working) else {
+            break;
+        };
+        
+        changes.push(change);
+        rest = working;
+        
+    }
+    
+    (changes, content.len() - rest.len())
+}
+
+fn decode_change(content: &mut &[u8]) -> Option<Change> {
+    let id = read_u64(content)?;
+    let timestamp = read_u64(content)?;
+    let reverts = Some(read_u64(content)?).filter(|&reverts| reverts != 0);
+    let tag = read_bytes(content)?;
+    let (before, renamed_from) = read_snapshot(content)?;
+    let (after, _) = read_snapshot(content)?;
+    
+    Some(Change {
+        id,
+        timestamp,
+        reverts,
+        tag,
+        renamed_from,
+        before,
+        after,
+    })
+}
+
+// none if truncated, some none if absent, along with the previous tag of renames
+fn read_snapshot(content: &mut &[u8]) -> Option<(Option<Snapshot>, Option<Vec<u8>>)> {
+    let (&present, rest) = content.split_first()?;
+    *content = rest;
+    
+    if present == 0 {
+        return Some((None, None));
+    }
+    
+    let value = read_u64(content)?;
+    let fields = read_bytes(content)?;
+    
+    let renamed_from = if present == 2 {
+        Some(read_bytes(content)?)
+    } else {
+        None
+    };
+    
+    Some((Some(Snapshot { value, fields }), renamed_from))
+}
+
+fn read_bytes(content: &mut &[u8]) -> Option<Vec<u8>> {
+    let length = usize::try_from(read_u64(content)?).ok()?;
+    let bytes = content.get(..length)?.to_vec();
+    *content = &content[length..];
+    Some(bytes)
+}
+
+fn read_u64(content: &mut &[u8]) -> Option<u64> {
+    let bytes = *content.first_chunk::<MEM_SIZE>()?;
+    *content = &content[MEM_SIZE..];
+    Some(u64::from_le_bytes(bytes))
+}
@@ -1,16 +1,46 @@
+mod conflict;
+mod corrupt_list;
+mod exchange;
+mod fields;
+mod format;
+mod journal;
+mod list_store;
+mod lock;
+mod order;
+mod persist;
+mod transaction;
+mod unicode;
+mod unicode_tables;
+
 use std::{
-    env,
+    collections::{ HashMap, HashSet },
     error::Error,
-    fs::{ self, File },
-    io::Write,
+    fs,
+    io::{ Read, Write },
     mem,
-    path::PathBuf,
+    ops::RangeBounds,
+    path::{ Path, PathBuf },
     str,
 };
 
+pub use conflict::Conflict;
+pub use corrupt_list::CorruptList;
+pub use exchange::{ Format, MergePolicy };
+pub use fields::{ Metadata, Status };
+pub use journal::{ Change, ChangeKind, Snapshot };
+pub use list_store::ListStore;
+pub use order::Order;
+pub use transaction::Transaction;
+
+use lock::Lock;
+
 pub struct List {
     path: PathBuf,
     content: Vec<u8>,
+    // incremented on every commit, used to detect changes made by other processes
+    generation: u64,
+    // case-folded tag to byte offset of its entry
+    index: HashMap<Vec<u8>, usize>,
 }
 
 pub struct ListIter<'c> {
@@ -20,6 +50,7 @@ pub struct ListIter<'c> {
 pub struct ListEntry<'c> {
     pub tag: &'c [u8],
     pub value: u64,
+    fields: &'c [u8],
 }
 
 impl List {
@@ -28,82 +59,276 @@ impl List {
     
     
     pub fn load(name: &str) -> Result<Self, Box<dyn Error>> {
-        let path = env::current_exe()?
-            .with_file_name(name)
-            .with_extension("ck");
+        ListStore::alongside_executable()?.load(name)
+    }
+    
+    pub(crate) fn open(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        // a commit interrupted halfway leaves the previous generation behind as a backup, restored in place of the main file
+        let mut decoded = match Self::read(&path) {
+            Err(error) if error.is::<CorruptList>() => Self::restore(&path).map_err(|_| error)?,
+            result => result?,
+        };
         
-        let content = fs::read(&path)
-            .map_err(|error| format!("Load of list file located at '{}' failed: '{}'", &path.to_string_lossy(), &error.to_string()))?;
+        // headerless or outdated files are rewritten in the current format right away, unless another process already did
+        let _lock = if decoded.migrated {
+            let lock = Lock::acquire(&path)?;
+            decoded = Self::read(&path)?;
+            Some(lock)
+        } else {
+            None
+        };
         
-        Ok(Self {
+        let mut list = Self {
             path,
-            content,
-        })
+            content: Vec::new(),
+            generation: decoded.generation,
+            index: HashMap::new(),
+        };
+        
+        if decoded.migrated {
+            list.write(decoded.records)?;
+        } else {
+            list.content = decoded.records;
+            list.reindex();
+        }
+        
+        Ok(list)
     }
     
     
     // -------------------- accessors --------------------
     
     
-    pub fn iter(&self) -> ListIter {
+    pub fn iter(&self) -> ListIter<'_> {
         ListIter { content: &self.content }
     }
     
+    pub fn get(&self, tag: &[u8]) -> Option<ListEntry<'_>> {
+        let offset = *self.index.get(&key(tag))?;
+        ListIter { content: &self.content[offset..] }.next()
+    }
     
-    // -------------------- mutators --------------------
-    
+    // entries shadowed by an earlier one with a tag comparing equal, which lists written under the former ASCII-only comparison can hold
+    pub fn collisions(&self) -> Vec<ListEntry<'_>> {
+        let mut seen = HashSet::new();
+        self.iter().filter(|entry| ! seen.insert(key(entry.tag))).collect()
+    }
     
-    pub fn insert(&mut self, tag: &[u8], value: u64) -> Result<(), Box<dyn Error>> {
-        if self.iter().any(|current| current.tag.eq_ignore_ascii_case(tag)) {
-            return Err("Tag in use".into());
+    pub fn sorted(&self, order: Order) -> Vec<ListEntry<'_>> {
+        let mut entries: Vec<ListEntry> = self.iter().collect();
+        
+        match order {
+            Order::Tag => entries.sort_by_cached_key(|entry| (key(entry.tag), entry.tag)),
+            Order::Value => entries.sort_by_cached_key(|entry| (entry.value, key(entry.tag), entry.tag)),
         }
         
-        let capacity = self.content.len() + (mem::size_of::<u64>() * 2 + tag.len());
-        let entries = self.iter()
-            .chain(Some(ListEntry { tag, value }));
+        entries
+    }
+    
+    // ordered by value
+    pub fn range<R: RangeBounds<u64>>(&self, values: R) -> Vec<ListEntry<'_>> {
+        let mut entries = self.sorted(Order::Value);
+        entries.retain(|entry| values.contains(&entry.value));
+        entries
+    }
+    
+    // ordered by tag, matching as lookups do
+    pub fn with_prefix(&self, prefix: &[u8]) -> Vec<ListEntry<'_>> {
+        let prefix = fold(prefix);
         
-        self.commit(Self::serialize(capacity, entries))
+        let mut entries = self.sorted(Order::Tag);
+        
+        entries.retain(|entry| {
+            let tag = key(entry.tag);
+            tag.starts_with(&prefix) && is_boundary(&tag, prefix.len())
+        });
+        
+        entries
     }
     
-    pub fn update(&mut self, tag: &[u8], value: u64) -> Result<(), Box<dyn Error>> {
-        let position = self.iter().position(|current| current.tag.eq_ignore_ascii_case(tag))
-            .ok_or("Tag not found")?;
+    // ordered by tag, matching as lookups do
+    pub fn containing(&self, needle: &[u8]) -> Vec<ListEntry<'_>> {
+        let needle = fold(needle);
+        
+        let mut entries = self.sorted(Order::Tag);
         
-        let capacity = self.content.len();
-        let entries = self.iter()
-            .enumerate()
-            .filter_map(|(current, entry)| (current != position).then_some(entry))
-            .chain(Some(ListEntry { tag, value }));
+        entries.retain(|entry| {
+            let tag = key(entry.tag);
+            (0..=tag.len().saturating_sub(needle.len()))
+                .filter(|&start| tag[start..].starts_with(&needle))
+                .any(|start| is_boundary(&tag, start) && is_boundary(&tag, start + needle.len()))
+        });
         
-        self.commit(Self::serialize(capacity, entries))
+        entries
+    }
+    
+    // the entry with the longest tag the text starts with, along with the length of the matching part of the text, trailing whitespace excluded
+    pub fn longest_prefix_of(&self, text: &[u8]) -> Option<(ListEntry<'_>, usize)> {
+        (0..=text.len())
+            .rev()
+            .filter(|&end| is_boundary(text, end))
+            .find_map(|end| self.get(&text[..end]).map(|entry| (entry, trim_end(&text[..end]).len())))
+    }
+    
+    pub fn contains(&self, tag: &[u8]) -> bool {
+        self.index.contains_key(&key(tag))
+    }
+    
+    // oldest first, limited to what survived the last compaction of the journal
+    pub fn history(&self) -> Result<Vec<Change>, Box<dyn Error>> {
+        journal::read(&self.path)
+    }
+    
+    pub fn export_to<W: Write>(&self, writer: W, format: Format) -> Result<(), Box<dyn Error>> {
+        exchange::export(self.iter(), writer, format)
+    }
+    
+    
+    // -------------------- mutators --------------------
+    
+    
+    pub fn transaction(&mut self) -> Transaction<'_> {
+        Transaction::new(self)
+    }
+    
+    pub fn insert(&mut self, tag: &[u8], value: u64) -> Result<(), Box<dyn Error>> {
+        let mut transaction = self.transaction();
+        transaction.insert(tag, value)?;
+        transaction.commit()
+    }
+    
+    pub fn update(&mut self, tag: &[u8], value: u64) -> Result<(), Box<dyn Error>> {
+        let mut transaction = self.transaction();
+        transaction.update(tag, value)?;
+        transaction.commit()
+    }
+    
+    pub fn update_metadata(&mut self, tag: &[u8], metadata: &Metadata) -> Result<(), Box<dyn Error>> {
+        let mut transaction = self.transaction();
+        transaction.update_metadata(tag, metadata)?;
+        transaction.commit()
     }
     
     pub fn delete(&mut self, tag: &[u8]) -> Result<(), Box<dyn Error>> {
-        let position = self.iter().position(|current| current.tag.eq_ignore_ascii_case(tag))
-            .ok_or("Tag not found")?;
+        let mut transaction = self.transaction();
+        transaction.delete(tag)?;
+        transaction.commit()
+    }
+    
+    pub fn rename(&mut self, tag: &[u8], new_tag: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut transaction = self.transaction();
+        transaction.rename(tag, new_tag)?;
+        transaction.commit()
+    }
+    
+    // reverts the latest changes not undone yet, newest first, returning how many were actually undone
+    pub fn undo(&mut self, count: usize) -> Result<usize, Box<dyn Error>> {
+        let history = journal::read(&self.path)?;
+        
+        // undoing is not itself undoable
+        let reverted: HashSet<u64> = history.iter()
+            .filter_map(|change| change.reverts)
+            .collect();
+        
+        let pending: Vec<&Change> = history.iter()
+            .rev()
+            .filter(|change| change.reverts.is_none() && ! reverted.contains(&change.id))
+            .take(count)
+            .collect();
+        
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        
+        let mut transaction = self.transaction();
+        
+        for change in &pending {
+            transaction.revert(change)?;
+        }
         
-        let capacity = self.content.len() - (mem::size_of::<u64>() * 2 + tag.len());
-        let entries = self.iter()
-            .enumerate()
-            .filter_map(|(current, entry)| (current != position).then_some(entry));
+        transaction.commit()?;
         
-        self.commit(Self::serialize(capacity, entries))
+        Ok(pending.len())
     }
     
-    fn commit(&mut self, content: Vec<u8>) -> Result<(), Box<dyn Error>> {
-        let tmp_path = chikuwa::EphemeralPath::builder()
-            .with_base(self.path.parent().ok_or("Invalid path")?)
-            .with_suffix(".tmp")
-            .build();
+    // nothing is written unless every entry could be parsed, returns the amount of entries inserted or updated
+    pub fn import_from<R: Read>(&mut self, reader: R, format: Format, policy: MergePolicy) -> Result<usize, Box<dyn Error>> {
+        let records = exchange::import(reader, format)?;
+        
+        // entries imported again keep the time they were first added, the first occurrence winning as with lookups
+        let mut added = HashMap::new();
+        
+        if policy == MergePolicy::Replace {
+            for entry in self.iter() {
+                if let Some(timestamp) = entry.added() {
+                    added.entry(key(entry.tag)).or_insert(timestamp);
+                }
+            }
+        }
         
-        File::create(&tmp_path)?.write_all(&content)?;
+        let mut transaction = self.transaction();
+        let mut imported = 0;
         
-        // attempt to perform the update atomically
-        fs::rename(&tmp_path, &self.path)?;
+        if policy == MergePolicy::Replace {
+            transaction.clear()?;
+        }
+        
+        // repeated tags within the imported entries are handled as if they were already in the list
+        for record in records {
+            
+            if ! transaction.contains(&record.tag) {
+                match added.get(&key(&record.tag)) {
+                    Some(&timestamp) => transaction.reinsert(&record.tag, record.value, timestamp)?,
+                    None => transaction.insert(&record.tag, record.value)?,
+                }
+            } else if policy == MergePolicy::Skip {
+                continue;
+            } else {
+                transaction.update(&record.tag, record.value)?;
+            }
+            
+            if let Some(metadata) = &record.metadata {
+                transaction.update_metadata(&record.tag, metadata)?;
+            }
+            
+            imported += 1;
+            
+        }
         
-        tmp_path.unmanage();
+        transaction.commit()?;
+        
+        Ok(imported)
+    }
+    
+    fn lock(&self) -> Result<Lock, Box<dyn Error>> {
+        Lock::acquire(&self.path)
+    }
+    
+    // true if another process committed since the list was loaded or last written
+    fn refresh(&mut self) -> Result<bool, Box<dyn Error>> {
+        let decoded = Self::read(&self.path)?;
+        
+        if decoded.generation == self.generation {
+            return Ok(false);
+        }
+        
+        self.content = decoded.records;
+        self.generation = decoded.generation;
+        self.reindex();
+        
+        Ok(true)
+    }
+    
+    // expects the lock to be held
+    fn write(&mut self, content: Vec<u8>) -> Result<(), Box<dyn Error>> {
+        let generation = self.generation.wrapping_add(1);
+        
+        persist::replace(&backup_path(&self.path), &fs::read(&self.path)?)?;
+        persist::replace(&self.path, &format::encode(&content, generation))?;
         
         self.content = content;
+        self.generation = generation;
+        self.reindex();
         
         Ok(())
     }
@@ -112,6 +337,54 @@ impl List {
     // -------------------- helpers --------------------
     
     
+    fn read(path: &Path) -> Result<format::Decoded, Box<dyn Error>> {
+        let content = fs::read(path)
+            .map_err(|error| format!("Load of list file located at '{}' failed: '{}'", &path.to_string_lossy(), &error.to_string()))?;
+        
+        // files are only empty before their first commit, which also creates the backup
+        if content.is_empty() && backup_path(path).exists() {
+            return Err(CorruptList { offset: 0, reason: "empty file" }.into());
+        }
+        
+        Ok(format::decode(content)?)
+    }
+    
+    fn restore(path: &Path) -> Result<format::Decoded, Box<dyn Error>> {
+        let _lock = Lock::acquire(path)?;
+        
+        // another process might have restored it in the meantime
+        if let Ok(decoded) = Self::read(path) {
+            return Ok(decoded);
+        }
+        
+        let backup = fs::read(backup_path(path))?;
+        let decoded = format::decode(backup.clone())?;
+        
+        persist::replace(path, &backup)?;
+        
+        Ok(decoded)
+    }
+    
+    fn reindex(&mut self) {
+        let mut index = HashMap::new();
+        let mut iter = self.iter();
+        
+        loop {
+            
+            let offset = self.content.len() - iter.content.len();
+            
+            let Some(entry) = iter.next() else {
+                break;
+            };
+            
+            // the first occurrence wins, as with the previous linear lookups
+            index.entry(key(entry.tag)).or_insert(offset);
+            
+        }
+        
+        self.index = index;
+    }
+    
     fn serialize<'c>(capacity: usize, entries: impl Iterator<Item = ListEntry<'c>>) -> Vec<u8> {
         let mut content = Vec::with_capacity(capacity);
         
@@ -119,6 +392,8 @@ impl List {
             content.extend_from_slice(&u64::try_from(entry.tag.len()).unwrap().to_le_bytes());
             content.extend_from_slice(entry.tag);
             content.extend_from_slice(&entry.value.to_le_bytes());
+            content.extend_from_slice(&u64::try_from(entry.fields.len()).unwrap().to_le_bytes());
+            content.extend_from_slice(entry.fields);
         }
         
         content
@@ -126,6 +401,42 @@ impl List {
     
 }
 
+// tags matching caselessly and up to canonical equivalence share the same key, the stored spelling being left untouched
+fn key(tag: &[u8]) -> Vec<u8> {
+    match str::from_utf8(tag) {
+        Ok(tag) => unicode::caseless(tag.trim()).into_bytes(),
+        // as UTF-8 correctness is not enforced, invalid tags only ignore ASCII case
+        Err(_) => tag.to_ascii_lowercase(),
+    }
+}
+
+// same folding as keys, without trimming, for searching within them
+fn fold(text: &[u8]) -> Vec<u8> {
+    match str::from_utf8(text) {
+        Ok(text) => unicode::caseless(text).into_bytes(),
+        Err(_) => text.to_ascii_lowercase(),
+    }
+}
+
+// same trimming as keys, which invalid tags are not subject to
+fn trim_end(text: &[u8]) -> &[u8] {
+    match str::from_utf8(text) {
+        Ok(text) => text.trim_end().as_bytes(),
+        Err(_) => text,
+    }
+}
+
+fn is_boundary(text: &[u8], at: usize) -> bool {
+    match str::from_utf8(text) {
+        Ok(text) => unicode::is_boundary(text, at),
+        Err(_) => true,
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("ck.bak")
+}
+
 impl <'c>IntoIterator for &'c List {
     
     type IntoIter = ListIter<'c>;
@@ -146,8 +457,8 @@ impl <'c>Iterator for ListIter<'c> {
         
         let mut working = self.content;
         
-        // bail if tag size cannot be represented as usize
-        let tag_size = usize::try_from(u64::from_le_bytes(*working.first_chunk::<MEM_SIZE>()?)).unwrap();
+        // records are validated on load, so anything unexpected can only be the end
+        let tag_size = usize::try_from(u64::from_le_bytes(*working.first_chunk::<MEM_SIZE>()?)).ok()?;
         working = &working[MEM_SIZE..];
         
         let tag = working.get(..tag_size)?;
@@ -156,12 +467,52 @@ impl <'c>Iterator for ListIter<'c> {
         let value = u64::from_le_bytes(*working.first_chunk::<MEM_SIZE>()?);
         working = &working[MEM_SIZE..];
         
+        let fields_size = usize::try_from(u64::from_le_bytes(*working.first_chunk::<MEM_SIZE>()?)).ok()?;
+        working = &working[MEM_SIZE..];
+        
+        let fields = working.get(..fields_size)?;
+        working = &working[fields_size..];
+        
         self.content = working;
         
         Some(ListEntry {
             tag,
             value,
+            fields,
         })
     }
     
 }
+
+impl ListEntry<'_> {
+    
+    pub fn metadata(&self) -> Metadata {
+        fields::metadata(self.fields)
+    }
+    
+    pub fn total(&self) -> Option<u64> {
+        fields::total(self.fields)
+    }
+    
+    pub fn status(&self) -> Option<Status> {
+        fields::status(self.fields)
+    }
+    
+    pub fn score(&self) -> Option<u64> {
+        fields::score(self.fields)
+    }
+    
+    pub fn notes(&self) -> Option<&[u8]> {
+        fields::notes(self.fields)
+    }
+    
+    // seconds since the unix epoch, missing for entries stored before timestamps were introduced
+    pub fn added(&self) -> Option<u64> {
+        fields::added(self.fields)
+    }
+    
+    pub fn updated(&self) -> Option<u64> {
+        fields::updated(self.fields)
+    }
+    
+}
@@ -0,0 +1,120 @@
+use std::{
+    env,
+    error::Error,
+    fs,
+    io,
+    path::{ Component, Path, PathBuf },
+};
+
+use super::{ List, format, lock::Lock, persist };
+
+const EXTENSION: &str = "ck";
+
+// every list is a file named after it, located in the base directory
+#[derive(Clone)]
+pub struct ListStore {
+    base: PathBuf,
+}
+
+impl ListStore {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new<P: AsRef<Path>>(base: P) -> Self {
+        Self {
+            base: base.as_ref().to_path_buf(),
+        }
+    }
+    
+    pub fn alongside_executable() -> Result<Self, Box<dyn Error>> {
+        let executable = env::current_exe()?;
+        let base = executable.parent().ok_or("Invalid executable path")?;
+        Ok(Self::new(base))
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+    
+    pub fn exists(&self, name: &str) -> bool {
+        self.path(name).is_ok_and(|path| path.is_file())
+    }
+    
+    // sorted, a missing base directory holding no lists
+    pub fn names(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let entries = match fs::read_dir(&self.base) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+        
+        let mut names = Vec::new();
+        
+        for entry in entries {
+            
+            let path = entry?.path();
+            
+            if ! path.is_file() || path.extension().and_then(|extension| extension.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            
+            if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                names.push(name.to_owned());
+            }
+            
+        }
+        
+        names.sort();
+        
+        Ok(names)
+    }
+    
+    
+    // -------------------- mutators --------------------
+    
+    
+    // missing lists are created empty, along with the base directory
+    pub fn load(&self, name: &str) -> Result<List, Box<dyn Error>> {
+        let path = self.path(name)?;
+        
+        if ! path.exists() {
+            self.create(&path)?;
+        }
+        
+        List::open(path)
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    // names cannot point outside of the base directory
+    fn path(&self, name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let mut components = Path::new(name).components();
+        
+        if ! matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+            return Err(format!("Invalid list name: '{}'", name).into());
+        }
+        
+        Ok(self.base.join(format!("{}.{}", name, EXTENSION)))
+    }
+    
+    fn create(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.base)?;
+        
+        let _lock = Lock::acquire(path)?;
+        
+        // another process might have created it in the meantime
+        if path.exists() {
+            return Ok(());
+        }
+        
+        persist::replace(path, &format::encode(&[], 0))
+    }
+    
+}
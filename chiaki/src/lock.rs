@@ -0,0 +1,177 @@
+use std::{
+    error::Error,
+    io,
+    mem,
+    os::raw::*,
+    path::Path,
+    ptr,
+    thread,
+    time::{ Duration, Instant },
+};
+
+mod ffi {
+    
+    use super::*;
+    
+    pub const GENERIC_READ: c_ulong = 0x8000_0000;
+    pub const GENERIC_WRITE: c_ulong = 0x4000_0000;
+    pub const FILE_SHARE_READ: c_ulong = 0x1;
+    pub const FILE_SHARE_WRITE: c_ulong = 0x2;
+    pub const FILE_SHARE_DELETE: c_ulong = 0x4;
+    pub const OPEN_ALWAYS: c_ulong = 4;
+    pub const FILE_ATTRIBUTE_NORMAL: c_ulong = 0x80;
+    pub const INVALID_HANDLE_VALUE: *mut c_void = -1_isize as *mut c_void;
+    
+    pub const LOCKFILE_FAIL_IMMEDIATELY: c_ulong = 0x1;
+    pub const LOCKFILE_EXCLUSIVE_LOCK: c_ulong = 0x2;
+    
+    pub const ERROR_LOCK_VIOLATION: i32 = 33;
+    
+    // https://learn.microsoft.com/en-us/windows/win32/api/minwinbase/ns-minwinbase-overlapped
+    #[repr(C)]
+    #[allow(clippy::upper_case_acronyms)]
+    pub struct OVERLAPPED {
+        pub internal: usize,
+        pub internal_high: usize,
+        pub offset: c_ulong,
+        pub offset_high: c_ulong,
+        pub h_event: *mut c_void,
+    }
+    
+    extern "system" {
+        
+        // https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-createfilew
+        pub fn CreateFileW(
+            lpFileName: *const c_ushort,
+            dwDesiredAccess: c_ulong,
+            dwShareMode: c_ulong,
+            lpSecurityAttributes: *mut c_void,
+            dwCreationDisposition: c_ulong,
+            dwFlagsAndAttributes: c_ulong,
+            hTemplateFile: *mut c_void,
+        ) -> *mut c_void;
+        
+        // https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-lockfileex
+        pub fn LockFileEx(
+            hFile: *mut c_void,
+            dwFlags: c_ulong,
+            dwReserved: c_ulong,
+            nNumberOfBytesToLockLow: c_ulong,
+            nNumberOfBytesToLockHigh: c_ulong,
+            lpOverlapped: *mut OVERLAPPED,
+        ) -> c_int;
+        
+        // https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-unlockfileex
+        pub fn UnlockFileEx(
+            hFile: *mut c_void,
+            dwReserved: c_ulong,
+            nNumberOfBytesToUnlockLow: c_ulong,
+            nNumberOfBytesToUnlockHigh: c_ulong,
+            lpOverlapped: *mut OVERLAPPED,
+        ) -> c_int;
+        
+        // https://learn.microsoft.com/en-us/windows/win32/api/handleapi/nf-handleapi-closehandle
+        pub fn CloseHandle(
+            hObject: *mut c_void,
+        ) -> c_int;
+        
+    }
+    
+}
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(25);
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+// advisory, only honored by processes going through this crate
+// held by the operating system on an open handle, so that it is released even if the process dies while holding it
+pub struct Lock {
+    handle: *mut c_void,
+}
+
+impl Lock {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn acquire(list_path: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = list_path.with_extension("ck.lock");
+        
+        // the file itself is left in place, as removing it would race with other processes opening it
+        let handle = unsafe {
+            
+            let handle = ffi::CreateFileW(
+                chikuwa::WinString::from(path.to_str().ok_or("Invalid path")?).as_ptr(),
+                ffi::GENERIC_READ | ffi::GENERIC_WRITE,
+                ffi::FILE_SHARE_READ | ffi::FILE_SHARE_WRITE | ffi::FILE_SHARE_DELETE,
+                ptr::null_mut(),
+                ffi::OPEN_ALWAYS,
+                ffi::FILE_ATTRIBUTE_NORMAL,
+                ptr::null_mut(),
+            );
+            
+            if handle == ffi::INVALID_HANDLE_VALUE {
+                return Err(io::Error::last_os_error().into());
+            }
+            
+            handle
+            
+        };
+        
+        // closes the handle on every early return
+        let lock = Self { handle };
+        
+        let started = Instant::now();
+        
+        loop {
+            
+            let result = unsafe {
+                
+                let mut overlapped: ffi::OVERLAPPED = mem::zeroed();
+                
+                ffi::LockFileEx(
+                    lock.handle,
+                    ffi::LOCKFILE_EXCLUSIVE_LOCK | ffi::LOCKFILE_FAIL_IMMEDIATELY,
+                    0,
+                    1,
+                    0,
+                    &mut overlapped,
+                )
+                
+            };
+            
+            if result != 0 {
+                return Ok(lock);
+            }
+            
+            let error = io::Error::last_os_error();
+            
+            if error.raw_os_error() != Some(ffi::ERROR_LOCK_VIOLATION) {
+                return Err(error.into());
+            }
+            
+            if started.elapsed() > TIMEOUT {
+                return Err(format!("List file located at '{}' is locked by another process", list_path.to_string_lossy()).into());
+            }
+            
+            thread::sleep(RETRY_INTERVAL);
+            
+        }
+    }
+    
+}
+
+impl Drop for Lock {
+    
+    fn drop(&mut self) {
+        unsafe {
+            
+            let mut overlapped: ffi::OVERLAPPED = mem::zeroed();
+            
+            // closing the handle would release it all the same, only later
+            ffi::UnlockFileEx(self.handle, 0, 1, 0, &mut overlapped);
+            ffi::CloseHandle(self.handle);
+            
+        }
+    }
+    
+}
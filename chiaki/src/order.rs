@@ -0,0 +1,7 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Order {
+    // compared as lookups are, ties being broken by the stored spelling
+    Tag,
+    // ties being broken by tag
+    Value,
+}
@@ -0,0 +1,59 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{ self, Write },
+    os::raw::*,
+    path::Path,
+};
+
+mod ffi {
+    
+    use super::*;
+    
+    pub const MOVEFILE_REPLACE_EXISTING: c_ulong = 0x1;
+    pub const MOVEFILE_WRITE_THROUGH: c_ulong = 0x8;
+    
+    extern "system" {
+        
+        // https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-movefileexw
+        pub fn MoveFileExW(
+            lpExistingFileName: *const c_ushort,
+            lpNewFileName: *const c_ushort,
+            dwFlags: c_ulong,
+        ) -> c_int;
+        
+    }
+    
+}
+
+// the content is flushed to disk before taking the place of the previous file, which is never left empty or partially written
+pub fn replace(path: &Path, content: &[u8]) -> Result<(), Box<dyn Error>> {
+    let tmp_path = chikuwa::EphemeralPath::builder()
+        .with_base(path.parent().ok_or("Invalid path")?)
+        .with_suffix(".tmp")
+        .build();
+    
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    drop(file);
+    
+    // does not return until the move is flushed to disk, the equivalent of syncing the parent directory
+    unsafe {
+        
+        let result = ffi::MoveFileExW(
+            chikuwa::WinString::from(tmp_path.to_str().ok_or("Invalid path")?).as_ptr(),
+            chikuwa::WinString::from(path.to_str().ok_or("Invalid path")?).as_ptr(),
+            ffi::MOVEFILE_REPLACE_EXISTING | ffi::MOVEFILE_WRITE_THROUGH,
+        );
+        
+        if result == 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        
+    }
+    
+    tmp_path.unmanage();
+    
+    Ok(())
+}
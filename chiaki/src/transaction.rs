@@ -0,0 +1,350 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    mem,
+};
+
+use super::{
+    Conflict, List, ListEntry, Metadata, fields, key,
+    journal::{ self, Change, Snapshot },
+};
+
+// changes are staged in memory and written to disk in a single commit, or discarded if never committed
+pub struct Transaction<'l> {
+    list: &'l mut List,
+    // generation of the list the staged entries are based on
+    generation: u64,
+    entries: Vec<Option<Staged>>,
+    index: HashMap<Vec<u8>, usize>,
+    // kept to be reapplied if another process commits first
+    operations: Vec<Operation>,
+    // recorded in the journal once committed
+    changes: Vec<Change>,
+}
+
+struct Staged {
+    tag: Vec<u8>,
+    value: u64,
+    fields: Vec<u8>,
+}
+
+enum Operation {
+    Insert(Vec<u8>, u64, Option<u64>),
+    Update(Vec<u8>, Option<u64>, Option<Metadata>),
+    Delete(Vec<u8>),
+    // deletes every entry by position, including those whose tags collide with earlier ones
+    Clear,
+    Rename(Vec<u8>, Vec<u8>),
+    // puts back the entry as it was before the change with the given id, under its previous tag if renamed, provided it is still as the change left it
+    Restore(Vec<u8>, Option<Vec<u8>>, Option<Snapshot>, Option<Snapshot>, u64),
+}
+
+impl<'l> Transaction<'l> {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub(crate) fn new(list: &'l mut List) -> Self {
+        let (entries, index) = view(list);
+        
+        Self {
+            generation: list.generation,
+            list,
+            entries,
+            index,
+            operations: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub fn contains(&self, tag: &[u8]) -> bool {
+        self.index.contains_key(&key(tag))
+    }
+    
+    
+    // -------------------- mutators --------------------
+    
+    
+    pub fn insert(&mut self, tag: &[u8], value: u64) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Insert(tag.to_vec(), value, None))
+    }
+    
+    // keeps the time an entry was added when inserting it again, as imports replacing the list do
+    pub(crate) fn reinsert(&mut self, tag: &[u8], value: u64, added: u64) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Insert(tag.to_vec(), value, Some(added)))
+    }
+    
+    pub fn update(&mut self, tag: &[u8], value: u64) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Update(tag.to_vec(), Some(value), None))
+    }
+    
+    pub fn update_metadata(&mut self, tag: &[u8], metadata: &Metadata) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Update(tag.to_vec(), None, Some(metadata.clone())))
+    }
+    
+    pub fn delete(&mut self, tag: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Delete(tag.to_vec()))
+    }
+    
+    pub(crate) fn clear(&mut self) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Clear)
+    }
+    
+    // renaming to a tag differing only by case or normalization is allowed
+    pub fn rename(&mut self, tag: &[u8], new_tag: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Rename(tag.to_vec(), new_tag.to_vec()))
+    }
+    
+    pub(crate) fn revert(&mut self, change: &Change) -> Result<(), Box<dyn Error>> {
+        self.stage(Operation::Restore(change.tag.clone(), change.renamed_from.clone(), change.before.clone(), change.after.clone(), change.id))
+    }
+    
+    // changes committed by another process since the list was loaded are kept, with these being reapplied on top of them
+    pub fn commit(mut self) -> Result<(), Box<dyn Error>> {
+        let _lock = self.list.lock()?;
+        
+        self.list.refresh()?;
+        
+        if self.generation != self.list.generation {
+            self.rebase()?;
+        }
+        
+        self.write()
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    // operations failing against a stale view are checked again against the latest committed content before being rejected
+    fn stage(&mut self, operation: Operation) -> Result<(), Box<dyn Error>> {
+        if self.apply(&operation).is_err() {
+            
+            let _lock = self.list.lock()?;
+            
+            self.list.refresh()?;
+            
+            if self.generation != self.list.generation {
+                self.rebase()?;
+            }
+            
+            self.apply(&operation)?;
+            
+        }
+        
+        self.operations.push(operation);
+        
+        Ok(())
+    }
+    
+    // replays the staged operations on top of the latest committed content, leaving the transaction untouched on conflict
+    fn rebase(&mut self) -> Result<(), Box<dyn Error>> {
+        let (entries, index) = view(self.list);
+        
+        let entries = mem::replace(&mut self.entries, entries);
+        let index = mem::replace(&mut self.index, index);
+        let changes = mem::take(&mut self.changes);
+        let operations = mem::take(&mut self.operations);
+        
+        let result = operations.iter().try_for_each(|operation| {
+            self.apply(operation).map_err(|reason| Conflict { tag: operation.tag().to_vec(), reason })
+        });
+        
+        self.operations = operations;
+        
+        if let Err(conflict) = result {
+            self.entries = entries;
+            self.index = index;
+            self.changes = changes;
+            return Err(conflict.into());
+        }
+        
+        self.generation = self.list.generation;
+        
+        Ok(())
+    }
+    
+    fn apply(&mut self, operation: &Operation) -> Result<(), &'static str> {
+        match operation {
+            
+            Operation::Insert(tag, value, added) => {
+                if self.contains(tag) {
+                    return Err("Tag in use");
+                }
+                let after = Snapshot {
+                    value: *value,
+                    fields: fields::fresh(*added),
+                };
+                self.push(tag, after.clone());
+                self.record(tag, None, None, Some(after), None);
+            },
+            
+            // updated entries are moved to the end of the list, keeping the spelling of their stored tag
+            Operation::Update(tag, value, metadata) => {
+                let (stored, before) = self.take(tag).ok_or("Tag not found")?;
+                // unknown fields and anything not being replaced are preserved
+                let after = Snapshot {
+                    value: value.unwrap_or(before.value),
+                    fields: fields::merge(&before.fields, metadata.as_ref()),
+                };
+                self.push(&stored, after.clone());
+                self.record(&stored, None, Some(before), Some(after), None);
+            },
+            
+            Operation::Delete(tag) => {
+                let (stored, before) = self.take(tag).ok_or("Tag not found")?;
+                self.record(&stored, None, Some(before), None, None);
+            },
+            
+            Operation::Clear => {
+                self.index.clear();
+                for entry in mem::take(&mut self.entries).into_iter().flatten() {
+                    let before = Snapshot {
+                        value: entry.value,
+                        fields: entry.fields,
+                    };
+                    self.record(&entry.tag, None, Some(before), None, None);
+                }
+            },
+            
+            // renamed entries are moved to the end of the list, as updated ones
+            Operation::Rename(tag, new_tag) => {
+                if key(tag) != key(new_tag) && self.contains(new_tag) {
+                    return Err("Tag in use");
+                }
+                let (stored, before) = self.take(tag).ok_or("Tag not found")?;
+                let after = Snapshot {
+                    value: before.value,
+                    fields: fields::merge(&before.fields, None),
+                };
+                self.push(new_tag, after.clone());
+                self.record(new_tag, Some(&stored), Some(before), Some(after), None);
+            },
+            
+            Operation::Restore(tag, renamed_from, snapshot, expected, reverts) => {
+                let restored = renamed_from.as_ref().unwrap_or(tag);
+                if key(restored) != key(tag) && self.contains(restored) {
+                    return Err("Tag in use");
+                }
+                // a later change to the entry, such as inserting it again after being deleted, is not overwritten
+                let current = self.snapshot(tag);
+                if current != *expected {
+                    return Err(if current.is_some() { "Tag in use" } else { "Tag not found" });
+                }
+                let before = self.take(tag).map(|(_, snapshot)| snapshot);
+                if before.is_none() && snapshot.is_none() {
+                    return Err("Tag not found");
+                }
+                if let Some(snapshot) = snapshot {
+                    self.push(restored, snapshot.clone());
+                }
+                // undoing a rename renames the entry back
+                let renamed_from = renamed_from.as_ref().filter(|_| before.is_some()).map(|_| tag.as_slice());
+                self.record(restored, renamed_from, before, snapshot.clone(), Some(*reverts));
+            },
+            
+        }
+        
+        Ok(())
+    }
+    
+    fn snapshot(&self, tag: &[u8]) -> Option<Snapshot> {
+        self.index.get(&key(tag))
+            .and_then(|&position| self.entries[position].as_ref())
+            .map(|entry| Snapshot {
+                value: entry.value,
+                fields: entry.fields.clone(),
+            })
+    }
+    
+    // the stored tag is returned along with the entry, as it might be spelled differently than the one given
+    fn take(&mut self, tag: &[u8]) -> Option<(Vec<u8>, Snapshot)> {
+        self.index.remove(&key(tag))
+            .and_then(|position| self.entries[position].take())
+            .map(|entry| (entry.tag, Snapshot {
+                value: entry.value,
+                fields: entry.fields,
+            }))
+    }
+    
+    fn push(&mut self, tag: &[u8], snapshot: Snapshot) {
+        self.index.insert(key(tag), self.entries.len());
+        self.entries.push(Some(Staged {
+            tag: tag.to_vec(),
+            value: snapshot.value,
+            fields: snapshot.fields,
+        }));
+    }
+    
+    // ids and timestamps are assigned when appended to the journal
+    fn record(&mut self, tag: &[u8], renamed_from: Option<&[u8]>, before: Option<Snapshot>, after: Option<Snapshot>, reverts: Option<u64>) {
+        self.changes.push(Change {
+            id: 0,
+            timestamp: 0,
+            reverts,
+            tag: tag.to_vec(),
+            renamed_from: renamed_from.map(<[u8]>::to_vec),
+            before,
+            after,
+        });
+    }
+    
+    // expects the lock to be held and the list to be up to date
+    fn write(self) -> Result<(), Box<dyn Error>> {
+        let capacity = self.entries.iter()
+            .flatten()
+            .map(|entry| mem::size_of::<u64>() * 3 + entry.tag.len() + entry.fields.len())
+            .sum();
+        
+        let entries = self.entries.iter()
+            .flatten()
+            .map(|entry| ListEntry {
+                tag: &entry.tag,
+                value: entry.value,
+                fields: &entry.fields,
+            });
+        
+        let content = List::serialize(capacity, entries);
+        
+        self.list.write(content)?;
+        
+        journal::append(&self.list.path, self.changes)
+    }
+    
+}
+
+fn view(list: &List) -> (Vec<Option<Staged>>, HashMap<Vec<u8>, usize>) {
+    let entries: Vec<Option<Staged>> = list.iter()
+        .map(|entry| Some(Staged {
+            tag: entry.tag.to_vec(),
+            value: entry.value,
+            fields: entry.fields.to_vec(),
+        }))
+        .collect();
+    
+    let mut index = HashMap::with_capacity(entries.len());
+    
+    // the first occurrence wins, as with lookups on the list itself
+    for (position, entry) in entries.iter().enumerate() {
+        if let Some(entry) = entry {
+            index.entry(key(&entry.tag)).or_insert(position);
+        }
+    }
+    
+    (entries, index)
+}
+
+impl Operation {
+    
+    fn tag(&self) -> &[u8] {
+        match self {
+            Self::Insert(tag, _, _) | Self::Update(tag, _, _) | Self::Delete(tag) | Self::Rename(tag, _) | Self::Restore(tag, _, _, _, _) => tag,
+            Self::Clear => &[],
+        }
+    }
+    
+}
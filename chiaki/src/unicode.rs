@@ -0,0 +1,95 @@
+use std::cmp::Ordering;
+
+use super::unicode_tables::{ CASE_FOLDING, COMBINING_CLASSES, DECOMPOSITIONS };
+
+// https://www.unicode.org/versions/Unicode14.0.0/ch03.pdf, section 3.12
+const HANGUL_S_BASE: u32 = 0xAC00;
+const HANGUL_L_BASE: u32 = 0x1100;
+const HANGUL_V_BASE: u32 = 0x1161;
+const HANGUL_T_BASE: u32 = 0x11A7;
+const HANGUL_T_COUNT: u32 = 28;
+const HANGUL_N_COUNT: u32 = 588;
+const HANGUL_S_COUNT: u32 = 11172;
+
+// canonical caseless matching (definition D145 of section 3.13)
+// canonically equivalent strings (e.g. NFC and NFD forms) share the same folding regardless of case
+pub fn caseless(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_ascii_lowercase();
+    }
+    
+    let mut folded = Vec::with_capacity(text.len());
+    
+    for current in decompose(text.chars()) {
+        match CASE_FOLDING.binary_search_by_key(&current, |&(code, _)| code) {
+            Ok(position) => folded.extend_from_slice(CASE_FOLDING[position].1),
+            Err(_) => folded.push(current),
+        }
+    }
+    
+    decompose(folded.into_iter()).into_iter().collect()
+}
+
+// whether a match ending at the given byte offset neither splits a character nor separates it from its combining marks
+pub fn is_boundary(text: &str, at: usize) -> bool {
+    text.is_char_boundary(at) && text[at..].chars().next().map_or(true, |current| combining_class(current) == 0)
+}
+
+// canonical decomposition followed by the canonical ordering of combining marks
+fn decompose(chars: impl Iterator<Item = char>) -> Vec<char> {
+    let mut decomposed = Vec::new();
+    
+    for current in chars {
+        
+        let code = current as u32;
+        
+        if (HANGUL_S_BASE..HANGUL_S_BASE + HANGUL_S_COUNT).contains(&code) {
+            let index = code - HANGUL_S_BASE;
+            let trailing = index % HANGUL_T_COUNT;
+            decomposed.extend(char::from_u32(HANGUL_L_BASE + index / HANGUL_N_COUNT));
+            decomposed.extend(char::from_u32(HANGUL_V_BASE + (index % HANGUL_N_COUNT) / HANGUL_T_COUNT));
+            if trailing != 0 {
+                decomposed.extend(char::from_u32(HANGUL_T_BASE + trailing));
+            }
+            continue;
+        }
+        
+        match DECOMPOSITIONS.binary_search_by_key(&current, |&(code, _)| code) {
+            Ok(position) => decomposed.extend_from_slice(DECOMPOSITIONS[position].1),
+            Err(_) => decomposed.push(current),
+        }
+        
+    }
+    
+    // every run of combining marks is stably sorted by combining class
+    let mut start = 0;
+    
+    while start < decomposed.len() {
+        
+        if combining_class(decomposed[start]) == 0 {
+            start += 1;
+            continue;
+        }
+        
+        let end = decomposed[start..].iter()
+            .position(|&current| combining_class(current) == 0)
+            .map_or(decomposed.len(), |length| start + length);
+        
+        decomposed[start..end].sort_by_key(|&current| combining_class(current));
+        
+        start = end;
+        
+    }
+    
+    decomposed
+}
+
+fn combining_class(current: char) -> u8 {
+    COMBINING_CLASSES
+        .binary_search_by(|&(first, last, _)| match (last < current, first > current) {
+            (true, _) => Ordering::Less,
+            (_, true) => Ordering::Greater,
+            _ => Ordering::Equal,
+        })
+        .map_or(0, |position| COMBINING_CLASSES[position].2)
+}
@@ -0,0 +1,57 @@
+use std::{ env, fs, thread, time::Duration };
+
+use chiaki::{ Format, ListStore, MergePolicy };
+
+fn store() -> (chikuwa::EphemeralPath, ListStore) {
+    let base = chikuwa::EphemeralPath::builder()
+        .with_base(env::temp_dir())
+        .with_suffix("-chiaki")
+        .build();
+    let store = ListStore::new(&base);
+    (base, store)
+}
+
+// headerless files hold the tag length, the tag and the value of every entry
+fn legacy_record(tag: &str, value: u64) -> Vec<u8> {
+    let mut record = Vec::new();
+    record.extend_from_slice(&(tag.len() as u64).to_le_bytes());
+    record.extend_from_slice(tag.as_bytes());
+    record.extend_from_slice(&value.to_le_bytes());
+    record
+}
+
+#[test]
+fn replace_clears_colliding_tags() {
+    let (base, store) = store();
+    
+    // written back when tags were only compared ignoring ASCII case
+    fs::create_dir_all(&base).unwrap();
+    fs::write(base.join("legacy.ck"), [legacy_record("Stra\u{DF}e", 1), legacy_record("STRASSE", 2)].concat()).unwrap();
+    
+    let mut list = store.load("legacy").unwrap();
+    list.import_from("3 Stra\u{DF}e\n".as_bytes(), Format::Text, MergePolicy::Replace).unwrap();
+    
+    let list = store.load("legacy").unwrap();
+    let entries: Vec<(Vec<u8>, u64)> = list.iter().map(|entry| (entry.tag.to_vec(), entry.value)).collect();
+    
+    assert_eq!(entries, [("Stra\u{DF}e".as_bytes().to_vec(), 3)]);
+}
+
+#[test]
+fn replace_keeps_added() {
+    let (_base, store) = store();
+    
+    let mut list = store.load("added").unwrap();
+    list.insert(b"Foo", 1).unwrap();
+    let added = list.get(b"Foo").unwrap().added();
+    
+    // timestamps have a resolution of a second
+    thread::sleep(Duration::from_millis(1100));
+    
+    list.import_from("2 foo\n3 Bar\n".as_bytes(), Format::Text, MergePolicy::Replace).unwrap();
+    
+    let list = store.load("added").unwrap();
+    
+    assert_eq!(list.get(b"Foo").unwrap().added(), added);
+    assert_ne!(list.get(b"Bar").unwrap().added(), added);
+}
@@ -0,0 +1,63 @@
+use std::{ env, fs };
+
+use chiaki::ListStore;
+
+fn store() -> (chikuwa::EphemeralPath, ListStore) {
+    let base = chikuwa::EphemeralPath::builder()
+        .with_base(env::temp_dir())
+        .with_suffix("-chiaki")
+        .build();
+    let store = ListStore::new(&base);
+    (base, store)
+}
+
+// headerless files hold the tag length, the tag and the value of every entry
+fn legacy_record(tag: &str, value: u64) -> Vec<u8> {
+    let mut record = Vec::new();
+    record.extend_from_slice(&(tag.len() as u64).to_le_bytes());
+    record.extend_from_slice(tag.as_bytes());
+    record.extend_from_slice(&value.to_le_bytes());
+    record
+}
+
+#[test]
+fn load_reports_collisions() {
+    let (base, store) = store();
+    
+    // written back when tags were only compared ignoring ASCII case
+    fs::create_dir_all(&base).unwrap();
+    fs::write(base.join("legacy.ck"), [legacy_record("Stra\u{DF}e", 1), legacy_record("STRASSE", 2), legacy_record("Foo", 3)].concat()).unwrap();
+    
+    let mut list = store.load("legacy").unwrap();
+    let collisions: Vec<&[u8]> = list.collisions().iter().map(|entry| entry.tag).collect();
+    
+    assert_eq!(collisions, ["STRASSE".as_bytes()]);
+    assert_eq!(list.get(b"strasse").unwrap().value, 1);
+    
+    // the shadowed entry is reached once the first one is gone
+    list.delete("Stra\u{DF}e".as_bytes()).unwrap();
+    
+    assert!(list.collisions().is_empty());
+    assert_eq!(list.get(b"strasse").unwrap().value, 2);
+}
+
+#[test]
+fn update_keeps_stored_spelling() {
+    let (_base, store) = store();
+    
+    let mut list = store.load("spelling").unwrap();
+    
+    // stored decomposed, updated through a precomposed and differently cased tag
+    list.insert("Sho\u{304}jo".as_bytes(), 1).unwrap();
+    list.update("SH\u{14C}JO".as_bytes(), 2).unwrap();
+    
+    let list = store.load("spelling").unwrap();
+    let entry = list.get("sh\u{14D}jo".as_bytes()).unwrap();
+    
+    assert_eq!(entry.tag, "Sho\u{304}jo".as_bytes());
+    assert_eq!(entry.value, 2);
+    
+    let history = list.history().unwrap();
+    
+    assert_eq!(history.last().unwrap().tag, "Sho\u{304}jo".as_bytes());
+}
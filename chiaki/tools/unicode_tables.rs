@@ -0,0 +1,142 @@
+// generates "src/unicode_tables.rs" from the following files of the Unicode Character Database, version 14.0.0
+// https://www.unicode.org/Public/14.0.0/ucd/UnicodeData.txt
+// https://www.unicode.org/Public/14.0.0/ucd/CaseFolding.txt
+
+// rustc -O tools/unicode_tables.rs -o ../target/unicode_tables
+// ../target/unicode_tables UnicodeData.txt CaseFolding.txt > src/unicode_tables.rs
+
+use std::{
+    collections::BTreeMap,
+    env,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    io::{ self, Write },
+};
+
+const UNICODE_VERSION: &str = "14.0.0";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut arguments = env::args().skip(1);
+    
+    let (Some(unicode_data), Some(case_folding)) = (arguments.next(), arguments.next()) else {
+        return Err("Usage: unicode_tables <UnicodeData.txt> <CaseFolding.txt>".into());
+    };
+    
+    let unicode_data = fs::read_to_string(unicode_data)?;
+    let case_folding = fs::read_to_string(case_folding)?;
+    
+    // -------------------- unicode data --------------------
+    
+    let mut decompositions = BTreeMap::new();
+    let mut classes: Vec<(u32, u32, u8)> = Vec::new();
+    
+    for line in unicode_data.lines().filter(|line| ! line.trim().is_empty()) {
+        
+        let fields: Vec<&str> = line.split(';').collect();
+        
+        if fields.len() < 6 {
+            return Err(format!("Invalid line: '{}'", line).into());
+        }
+        
+        let code = parse(fields[0])?;
+        let class: u8 = fields[3].parse()?;
+        
+        // consecutive code points sharing a class are merged into a single range
+        match classes.last_mut() {
+            _ if class == 0 => (),
+            Some(range) if range.1 + 1 == code && range.2 == class => range.1 = code,
+            _ => classes.push((code, code, class)),
+        }
+        
+        // compatibility decompositions are tagged, such as "<compat>" or "<font>"
+        if ! fields[5].is_empty() && ! fields[5].starts_with('<') {
+            decompositions.insert(code, fields[5].split(' ').map(parse).collect::<Result<Vec<_>, _>>()?);
+        }
+        
+    }
+    
+    // -------------------- case folding --------------------
+    
+    let mut foldings = Vec::new();
+    
+    for line in case_folding.lines() {
+        
+        let line = line.split('#').next().unwrap_or_default();
+        
+        if line.trim().is_empty() {
+            continue;
+        }
+        
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        
+        if fields.len() < 3 {
+            return Err(format!("Invalid line: '{}'", line).into());
+        }
+        
+        // simple ("S") and turkic ("T") foldings are left out
+        if matches!(fields[1], "C" | "F") {
+            foldings.push((parse(fields[0])?, fields[2].split(' ').map(parse).collect::<Result<Vec<_>, _>>()?));
+        }
+        
+    }
+    
+    // -------------------- output --------------------
+    
+    let mut output = String::new();
+    
+    writeln!(output, "// generated by tools/unicode_tables.rs from the Unicode Character Database, version {}\r", UNICODE_VERSION)?;
+    writeln!(output, "\r")?;
+    writeln!(output, "// full canonical decompositions, hangul syllables excluded as they are decomposed algorithmically\r")?;
+    writeln!(output, "pub const DECOMPOSITIONS: &[(char, &[char])] = &[\r")?;
+    
+    for &code in decompositions.keys() {
+        let mut full = Vec::new();
+        decompose(code, &decompositions, &mut full);
+        writeln!(output, "    ({}, {}),\r", literal(code), sequence(&full))?;
+    }
+    
+    writeln!(output, "];\r")?;
+    writeln!(output, "\r")?;
+    writeln!(output, "// ranges of code points sharing a non-zero canonical combining class\r")?;
+    writeln!(output, "pub const COMBINING_CLASSES: &[(char, char, u8)] = &[\r")?;
+    
+    for (first, last, class) in classes {
+        writeln!(output, "    ({}, {}, {}),\r", literal(first), literal(last), class)?;
+    }
+    
+    writeln!(output, "];\r")?;
+    writeln!(output, "\r")?;
+    writeln!(output, "// full case folding, statuses \"C\" and \"F\"\r")?;
+    writeln!(output, "pub const CASE_FOLDING: &[(char, &[char])] = &[\r")?;
+    
+    for (code, folding) in foldings {
+        writeln!(output, "    ({}, {}),\r", literal(code), sequence(&folding))?;
+    }
+    
+    writeln!(output, "];\r")?;
+    
+    io::stdout().write_all(output.as_bytes())?;
+    
+    Ok(())
+}
+
+fn parse(code: &str) -> Result<u32, Box<dyn Error>> {
+    u32::from_str_radix(code.trim(), 16).map_err(|_| format!("Invalid code point: '{}'", code).into())
+}
+
+// canonical decompositions are applied recursively until none is left
+fn decompose(code: u32, decompositions: &BTreeMap<u32, Vec<u32>>, output: &mut Vec<u32>) {
+    match decompositions.get(&code) {
+        Some(decomposition) => decomposition.iter().for_each(|&code| decompose(code, decompositions, output)),
+        None => output.push(code),
+    }
+}
+
+fn literal(code: u32) -> String {
+    format!("'\\u{{{:X}}}'", code)
+}
+
+fn sequence(codes: &[u32]) -> String {
+    format!("&[{}]", codes.iter().map(|&code| literal(code)).collect::<Vec<_>>().join(", "))
+}
@@ -0,0 +1,61 @@
+// https://www.rfc-editor.org/rfc/rfc1952#section-8
+
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    
+    while index < 256 {
+        
+        #[allow(clippy::cast_possible_truncation)]
+        let mut value = index as u32;
+        let mut bit = 0;
+        
+        while bit < 8 {
+            value = if value & 1 == 1 { POLYNOMIAL ^ (value >> 1) } else { value >> 1 };
+            bit += 1;
+        }
+        
+        table[index] = value;
+        index += 1;
+        
+    }
+    
+    table
+};
+
+#[derive(Clone, Copy)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    
+    fn default() -> Self {
+        Self::new()
+    }
+    
+}
+
+impl Crc32 {
+    
+    pub fn new() -> Self {
+        Self(u32::MAX)
+    }
+    
+    pub fn checksum(data: &[u8]) -> u32 {
+        let mut crc = Self::new();
+        crc.update(data);
+        crc.finish()
+    }
+    
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.0 = TABLE[((self.0 ^ u32::from(byte)) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+    
+    pub fn finish(&self) -> u32 {
+        self.0 ^ u32::MAX
+    }
+    
+}
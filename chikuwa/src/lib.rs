@@ -2,8 +2,10 @@ mod ephemeral_path;
 mod win_string;
 mod subslice_range;
 mod html_escaper;
+mod crc32;
 
 pub use ephemeral_path::EphemeralPath;
 pub use win_string::WinString;
 pub use subslice_range::subslice_range;
 pub use html_escaper::HtmlEscaper;
+pub use crc32::Crc32;
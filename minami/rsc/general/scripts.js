@@ -22,6 +22,10 @@ const ACTIONS_URL_ATTRIBUTE = "data-url";
 const ACTIONS_CONFIRM_ATTRIBUTE = "data-confirm";
 const ACTIONS_PROMPT_ATTRIBUTE = "data-prompt";
 const ACTIONS_REFRESH_ATTRIBUTE = "data-refresh";
+const ACTIONS_TRANSFER_ATTRIBUTE = "data-transfer";
+
+const TRANSFER_FORMATS = { csv: "csv", json: "json", text: "txt" };
+const TRANSFER_POLICIES = ["skip", "overwrite", "replace"];
 
 const TOGGLES_NODE_SELECTOR = ".toggles";
 
@@ -360,6 +364,15 @@ class Actions {
                 const confirm = child.getAttribute(ACTIONS_CONFIRM_ATTRIBUTE) === "true";
                 const prompt = child.getAttribute(ACTIONS_PROMPT_ATTRIBUTE) === "true";
                 const refresh = child.getAttribute(ACTIONS_REFRESH_ATTRIBUTE) === "true";
+                const transfer = child.getAttribute(ACTIONS_TRANSFER_ATTRIBUTE);
+                
+                if (transfer === "export") {
+                    return this.export(url);
+                }
+                
+                if (transfer === "import") {
+                    return this.import(url, refresh);
+                }
                 
                 this.request(url, confirm, prompt, refresh);
                 
@@ -416,6 +429,123 @@ class Actions {
         
     };
     
+    export = (url) => {
+        
+        // -------------------- format --------------------
+        
+        const format = window.prompt(`The list will be exported as ${Object.keys(TRANSFER_FORMATS).join(", ")}`, "csv");
+        
+        if (format === null) {
+            return;
+        }
+        
+        if (! Object.hasOwn(TRANSFER_FORMATS, format)) {
+            window.alert(`Unknown list format '${format}'`);
+            return;
+        }
+        
+        // -------------------- request --------------------
+        
+        fetch(`${url}/${format}`)
+            .then(response => {
+                
+                if (response.status != 200) {
+                    response.text().then(error => window.alert(error));
+                    return;
+                }
+                
+                // named after the list, as in "/watchlist/export"
+                
+                response.blob().then(blob => {
+                    const link = document.createElement("a");
+                    link.href = URL.createObjectURL(blob);
+                    link.download = `${url.split("/")[1]}.${TRANSFER_FORMATS[format]}`;
+                    link.click();
+                    URL.revokeObjectURL(link.href);
+                });
+                
+            })
+            .catch(error => window.alert(error));
+        
+    };
+    
+    import = (url, refresh) => {
+        
+        const input = document.createElement("input");
+        input.type = "file";
+        input.accept = Object.values(TRANSFER_FORMATS).map(extension => `.${extension}`).join(",");
+        
+        input.addEventListener("change", () => {
+            
+            // -------------------- format --------------------
+            
+            const file = input.files[0];
+            
+            if (file === undefined) {
+                return;
+            }
+            
+            const extension = file.name.split(".").pop().toLowerCase();
+            const format = Object.keys(TRANSFER_FORMATS).find(format => TRANSFER_FORMATS[format] === extension);
+            
+            if (format === undefined) {
+                window.alert(`Unknown list format '${extension}'`);
+                return;
+            }
+            
+            // -------------------- policy --------------------
+            
+            const policy = window.prompt(`Entries already in the list will be handled with ${TRANSFER_POLICIES.join(", ")}`, "skip");
+            
+            if (policy === null) {
+                return;
+            }
+            
+            if (! TRANSFER_POLICIES.includes(policy)) {
+                window.alert(`Unknown merge policy '${policy}'`);
+                return;
+            }
+            
+            if (policy === "replace" && ! window.confirm("Every entry not being imported will be deleted, are you sure you want to proceed?")) {
+                return;
+            }
+            
+            // -------------------- request --------------------
+            
+            // sent as text, as file parts are not supported by the server
+            
+            file.text()
+                .then(content => {
+                    
+                    const form_data = new FormData();
+                    
+                    form_data.append("input", content);
+                    form_data.append("format", format);
+                    form_data.append("policy", policy);
+                    
+                    return fetch(url, { method: "POST", body: form_data });
+                    
+                })
+                .then(response => {
+                    
+                    if (response.status != 200) {
+                        response.text().then(error => window.alert(error));
+                        return;
+                    }
+                    
+                    if (refresh) {
+                        this.parent.list.refresh();
+                    }
+                    
+                })
+                .catch(error => window.alert(error));
+            
+        });
+        
+        input.click();
+        
+    };
+    
 }
 
 class Toggles {
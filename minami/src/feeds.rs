@@ -1,6 +1,7 @@
 use std::{
     error::Error,
     io::Write,
+    str,
 };
 
 use super::{ Request, StatusCode, ContentType, CacheControl };
@@ -12,6 +13,9 @@ pub enum FeedsEndpoint {
     Entries,
     Insert,
     Delete,
+    Undo,
+    Export(chiaki::Format),
+    Import,
 }
 
 impl FeedsEndpoint {
@@ -22,6 +26,11 @@ impl FeedsEndpoint {
             (b"GET", b"/feeds/entries") => Some(Self::Entries),
             (b"POST", b"/feeds/insert") => Some(Self::Insert),
             (b"POST", b"/feeds/delete") => Some(Self::Delete),
+            (b"POST", b"/feeds/undo") => Some(Self::Undo),
+            (b"GET", b"/feeds/export/csv") => Some(Self::Export(chiaki::Format::Csv)),
+            (b"GET", b"/feeds/export/json") => Some(Self::Export(chiaki::Format::Json)),
+            (b"GET", b"/feeds/export/text") => Some(Self::Export(chiaki::Format::Text)),
+            (b"POST", b"/feeds/import") => Some(Self::Import),
             _ => None,
         }
     }
@@ -32,6 +41,9 @@ impl FeedsEndpoint {
             Self::Entries => entries(&mut request),
             Self::Insert => insert(&mut request),
             Self::Delete => delete(&mut request),
+            Self::Undo => undo(&mut request),
+            Self::Export(format) => export(&mut request, *format),
+            Self::Import => import(&mut request),
         };
         
         if let Err(error) = result {
@@ -53,7 +65,7 @@ fn index(request: &mut Request) -> Result<(), Box<dyn Error>> {
 fn entries(request: &mut Request) -> Result<(), Box<dyn Error>> {
     // -------------------- list --------------------
     
-    let feeds = chiaki::List::load("feeds")?;
+    let feeds = super::load_list("feeds")?;
     
     // -------------------- response --------------------
     
@@ -82,7 +94,7 @@ fn insert(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("feeds")
+    super::load_list("feeds")
         .and_then(|mut list| list.insert(url, 0))?;
     
     // -------------------- response --------------------
@@ -102,7 +114,7 @@ fn delete(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("feeds")
+    super::load_list("feeds")
         .and_then(|mut list| list.delete(url))?;
     
     // -------------------- response --------------------
@@ -112,3 +124,79 @@ fn delete(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     Ok(())
 }
+
+fn undo(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- operation --------------------
+    
+    let undone = super::load_list("feeds")
+        .and_then(|mut list| list.undo(1))?;
+    
+    if undone == 0 {
+        return Err("Nothing to undo".into());
+    }
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
+
+fn export(request: &mut Request, format: chiaki::Format) -> Result<(), Box<dyn Error>> {
+    // -------------------- list --------------------
+    
+    let feeds = super::load_list("feeds")?;
+    
+    // -------------------- content --------------------
+    
+    // fully built before responding, so that failures can still be reported
+    let mut content = Vec::new();
+    
+    feeds.export_to(&mut content, format)?;
+    
+    // -------------------- response --------------------
+    
+    let content_type = match format {
+        chiaki::Format::Csv => ContentType::Csv,
+        chiaki::Format::Json => ContentType::Json,
+        chiaki::Format::Text => ContentType::Plain,
+    };
+    
+    request.start_response(StatusCode::Ok, content_type, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(&content))?;
+    
+    Ok(())
+}
+
+fn import(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- content, format and policy --------------------
+    
+    let content = request.param(b"input")
+        .next()
+        .ok_or("Content not provided")?;
+    
+    let format: chiaki::Format = request.param(b"format")
+        .next()
+        .and_then(|format| str::from_utf8(format).ok())
+        .ok_or("Format not provided")?
+        .parse()?;
+    
+    let policy: chiaki::MergePolicy = request.param(b"policy")
+        .next()
+        .and_then(|policy| str::from_utf8(policy).ok())
+        .ok_or("Merge policy not provided")?
+        .parse()?;
+    
+    // -------------------- operation --------------------
+    
+    super::load_list("feeds")
+        .and_then(|mut list| list.import_from(content, format, policy))?;
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
@@ -20,6 +20,22 @@ use general::GeneralEndpoint;
 use ayano::{ Server, Request, StatusCode, ContentType, CacheControl };
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // checked upfront, as most parameters are only accessed once requests come in
+    rin::register(rin::Schema::new()
+        .with_required(b"address", rin::Kind::Text)
+        .with_required(b"root", rin::Kind::Path)
+        .with_required(b"flag", rin::Kind::Text)
+        .with_required(b"player", rin::Kind::Path)
+        .with_optional(b"lists", rin::Kind::Path)
+        .with_default(b"reload", rin::Kind::Duration, "5s"))?;
+    
+    // parameters are read again on every request, so that changes apply without restarting
+    let reload = rin::get_duration(b"reload")?;
+    
+    if ! reload.is_zero() {
+        rin::watch(reload)?;
+    }
+    
     for mut request in Server::new(rin::get(b"address")?)? {
         
         let Some(resource) = request.resource() else {
@@ -59,3 +75,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     
     Ok(())
 }
+
+// optional, falling back to the directory of the executable
+fn load_list(name: &str) -> Result<chiaki::List, Box<dyn Error>> {
+    let store = match rin::get_path(b"lists") {
+        Ok(base) => chiaki::ListStore::new(base),
+        Err(_) => chiaki::ListStore::alongside_executable()?,
+    };
+    
+    store.load(name)
+}
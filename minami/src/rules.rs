@@ -14,6 +14,10 @@ pub enum RulesEndpoint {
     Insert,
     Update,
     Delete,
+    Rename,
+    Undo,
+    Export(chiaki::Format),
+    Import,
 }
 
 impl RulesEndpoint {
@@ -25,6 +29,12 @@ impl RulesEndpoint {
             (b"POST", b"/rules/insert") => Some(Self::Insert),
             (b"POST", b"/rules/update") => Some(Self::Update),
             (b"POST", b"/rules/delete") => Some(Self::Delete),
+            (b"POST", b"/rules/rename") => Some(Self::Rename),
+            (b"POST", b"/rules/undo") => Some(Self::Undo),
+            (b"GET", b"/rules/export/csv") => Some(Self::Export(chiaki::Format::Csv)),
+            (b"GET", b"/rules/export/json") => Some(Self::Export(chiaki::Format::Json)),
+            (b"GET", b"/rules/export/text") => Some(Self::Export(chiaki::Format::Text)),
+            (b"POST", b"/rules/import") => Some(Self::Import),
             _ => None,
         }
     }
@@ -36,6 +46,10 @@ impl RulesEndpoint {
             Self::Insert => insert(&mut request),
             Self::Update => update(&mut request),
             Self::Delete => delete(&mut request),
+            Self::Rename => rename(&mut request),
+            Self::Undo => undo(&mut request),
+            Self::Export(format) => export(&mut request, *format),
+            Self::Import => import(&mut request),
         };
         
         if let Err(error) = result {
@@ -57,7 +71,7 @@ fn index(request: &mut Request) -> Result<(), Box<dyn Error>> {
 fn entries(request: &mut Request) -> Result<(), Box<dyn Error>> {
     // -------------------- list --------------------
     
-    let rules = chiaki::List::load("rules")?;
+    let rules = super::load_list("rules")?;
     
     // -------------------- response --------------------
     
@@ -86,7 +100,7 @@ fn insert(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("rules")
+    super::load_list("rules")
         .and_then(|mut list| list.insert(matcher, 0))?;
     
     // -------------------- response --------------------
@@ -112,7 +126,7 @@ fn update(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("rules")
+    super::load_list("rules")
         .and_then(|mut list| list.update(matcher, progress))?;
     
     // -------------------- response --------------------
@@ -132,7 +146,7 @@ fn delete(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("rules")
+    super::load_list("rules")
         .and_then(|mut list| list.delete(matcher))?;
     
     // -------------------- response --------------------
@@ -142,3 +156,103 @@ fn delete(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     Ok(())
 }
+
+fn rename(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- current and new matcher --------------------
+    
+    let matcher = request.param(b"tag")
+        .next()
+        .ok_or("Matcher not provided")?;
+    
+    let renamed = request.param(b"input")
+        .next()
+        .ok_or("New matcher not provided")?;
+    
+    // -------------------- operation --------------------
+    
+    super::load_list("rules")
+        .and_then(|mut list| list.rename(matcher, renamed))?;
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
+
+fn undo(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- operation --------------------
+    
+    let undone = super::load_list("rules")
+        .and_then(|mut list| list.undo(1))?;
+    
+    if undone == 0 {
+        return Err("Nothing to undo".into());
+    }
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
+
+fn export(request: &mut Request, format: chiaki::Format) -> Result<(), Box<dyn Error>> {
+    // -------------------- list --------------------
+    
+    let rules = super::load_list("rules")?;
+    
+    // -------------------- content --------------------
+    
+    // fully built before responding, so that failures can still be reported
+    let mut content = Vec::new();
+    
+    rules.export_to(&mut content, format)?;
+    
+    // -------------------- response --------------------
+    
+    let content_type = match format {
+        chiaki::Format::Csv => ContentType::Csv,
+        chiaki::Format::Json => ContentType::Json,
+        chiaki::Format::Text => ContentType::Plain,
+    };
+    
+    request.start_response(StatusCode::Ok, content_type, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(&content))?;
+    
+    Ok(())
+}
+
+fn import(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- content, format and policy --------------------
+    
+    let content = request.param(b"input")
+        .next()
+        .ok_or("Content not provided")?;
+    
+    let format: chiaki::Format = request.param(b"format")
+        .next()
+        .and_then(|format| str::from_utf8(format).ok())
+        .ok_or("Format not provided")?
+        .parse()?;
+    
+    let policy: chiaki::MergePolicy = request.param(b"policy")
+        .next()
+        .and_then(|policy| str::from_utf8(policy).ok())
+        .ok_or("Merge policy not provided")?
+        .parse()?;
+    
+    // -------------------- operation --------------------
+    
+    super::load_list("rules")
+        .and_then(|mut list| list.import_from(content, format, policy))?;
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
@@ -14,6 +14,10 @@ pub enum WatchlistEndpoint {
     Insert,
     Update,
     Delete,
+    Rename,
+    Undo,
+    Export(chiaki::Format),
+    Import,
 }
 
 impl WatchlistEndpoint {
@@ -25,6 +29,12 @@ impl WatchlistEndpoint {
             (b"POST", b"/watchlist/insert") => Some(Self::Insert),
             (b"POST", b"/watchlist/update") => Some(Self::Update),
             (b"POST", b"/watchlist/delete") => Some(Self::Delete),
+            (b"POST", b"/watchlist/rename") => Some(Self::Rename),
+            (b"POST", b"/watchlist/undo") => Some(Self::Undo),
+            (b"GET", b"/watchlist/export/csv") => Some(Self::Export(chiaki::Format::Csv)),
+            (b"GET", b"/watchlist/export/json") => Some(Self::Export(chiaki::Format::Json)),
+            (b"GET", b"/watchlist/export/text") => Some(Self::Export(chiaki::Format::Text)),
+            (b"POST", b"/watchlist/import") => Some(Self::Import),
             _ => None,
         }
     }
@@ -36,6 +46,10 @@ impl WatchlistEndpoint {
             Self::Insert => insert(&mut request),
             Self::Update => update(&mut request),
             Self::Delete => delete(&mut request),
+            Self::Rename => rename(&mut request),
+            Self::Undo => undo(&mut request),
+            Self::Export(format) => export(&mut request, *format),
+            Self::Import => import(&mut request),
         };
         
         if let Err(error) = result {
@@ -57,7 +71,7 @@ fn index(request: &mut Request) -> Result<(), Box<dyn Error>> {
 fn entries(request: &mut Request) -> Result<(), Box<dyn Error>> {
     // -------------------- list --------------------
     
-    let watchlist = chiaki::List::load("watchlist")?;
+    let watchlist = super::load_list("watchlist")?;
     
     // -------------------- response --------------------
     
@@ -86,7 +100,7 @@ fn insert(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("watchlist")
+    super::load_list("watchlist")
         .and_then(|mut list| list.insert(title, 0))?;
     
     // -------------------- response --------------------
@@ -112,7 +126,7 @@ fn update(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("watchlist")
+    super::load_list("watchlist")
         .and_then(|mut list| list.update(title, progress))?;
     
     // -------------------- response --------------------
@@ -132,7 +146,7 @@ fn delete(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     // -------------------- operation --------------------
     
-    chiaki::List::load("watchlist")
+    super::load_list("watchlist")
         .and_then(|mut list| list.delete(title))?;
     
     // -------------------- response --------------------
@@ -142,3 +156,103 @@ fn delete(request: &mut Request) -> Result<(), Box<dyn Error>> {
     
     Ok(())
 }
+
+fn rename(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- current and new title --------------------
+    
+    let title = request.param(b"tag")
+        .next()
+        .ok_or("Title not provided")?;
+    
+    let renamed = request.param(b"input")
+        .next()
+        .ok_or("New title not provided")?;
+    
+    // -------------------- operation --------------------
+    
+    super::load_list("watchlist")
+        .and_then(|mut list| list.rename(title, renamed))?;
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
+
+fn undo(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- operation --------------------
+    
+    let undone = super::load_list("watchlist")
+        .and_then(|mut list| list.undo(1))?;
+    
+    if undone == 0 {
+        return Err("Nothing to undo".into());
+    }
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
+
+fn export(request: &mut Request, format: chiaki::Format) -> Result<(), Box<dyn Error>> {
+    // -------------------- list --------------------
+    
+    let watchlist = super::load_list("watchlist")?;
+    
+    // -------------------- content --------------------
+    
+    // fully built before responding, so that failures can still be reported
+    let mut content = Vec::new();
+    
+    watchlist.export_to(&mut content, format)?;
+    
+    // -------------------- response --------------------
+    
+    let content_type = match format {
+        chiaki::Format::Csv => ContentType::Csv,
+        chiaki::Format::Json => ContentType::Json,
+        chiaki::Format::Text => ContentType::Plain,
+    };
+    
+    request.start_response(StatusCode::Ok, content_type, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(&content))?;
+    
+    Ok(())
+}
+
+fn import(request: &mut Request) -> Result<(), Box<dyn Error>> {
+    // -------------------- content, format and policy --------------------
+    
+    let content = request.param(b"input")
+        .next()
+        .ok_or("Content not provided")?;
+    
+    let format: chiaki::Format = request.param(b"format")
+        .next()
+        .and_then(|format| str::from_utf8(format).ok())
+        .ok_or("Format not provided")?
+        .parse()?;
+    
+    let policy: chiaki::MergePolicy = request.param(b"policy")
+        .next()
+        .and_then(|policy| str::from_utf8(policy).ok())
+        .ok_or("Merge policy not provided")?
+        .parse()?;
+    
+    // -------------------- operation --------------------
+    
+    super::load_list("watchlist")
+        .and_then(|mut list| list.import_from(content, format, policy))?;
+    
+    // -------------------- response --------------------
+    
+    request.start_response(StatusCode::Ok, ContentType::Plain, CacheControl::Dynamic)
+        .and_then(|mut response| response.write_all(b"OK"))?;
+    
+    Ok(())
+}
@@ -1,20 +1,22 @@
 mod releases;
 
 use std::{
+    env,
     error::Error,
     ffi::OsString,
     fs,
-    io::{ self, Read, Write, BufWriter, },
+    io::{ self, Read, Write },
     path::{ Path, PathBuf },
     str,
 };
 
-use releases::{ Releases, ReleasesEntry };
+use releases::Releases;
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const TORRENT_FILE_WRITER_BUFFER_SIZE: usize = 64 * 1024;
+// far beyond any legitimate torrent file
+const TORRENT_FILE_SIZE_LIMIT: u64 = 16 * 1024 * 1024;
 
 fn main() {
     println!("{} v{}", APP_NAME, APP_VERSION);
@@ -37,41 +39,131 @@ fn process() -> Result<(), Box<dyn Error>> {
     println!();
     println!("Loading configuration...");
     
-    let folder = rin::get(b"folder")?;
+    rin::register(rin::Schema::new()
+        .with_required(b"folder", rin::Kind::Path)
+        .with_optional(b"proxy", rin::Kind::Text)
+        .with_default(b"bypass", rin::Kind::Text, "")
+        .with_optional(b"user_agent", rin::Kind::Text)
+        .with_optional(b"cookies", rin::Kind::Path)
+        .with_optional(b"lists", rin::Kind::Path))?;
+    
+    let folder = rin::get_path(b"folder")?;
+    
+    // optional, falling back to the environment variables
+    let proxy = match rin::get(b"proxy") {
+        Ok(url) => Some(akari::Proxy::parse(url)?.with_bypass(rin::get(b"bypass")?)),
+        Err(_) => akari::Proxy::from_env()?,
+    };
+    
+    let user_agent = rin::get(b"user_agent").ok();
+    let cookies = rin::get_path(b"cookies").ok();
+    
+    // optional, falling back to the directory of the executable
+    let lists = match rin::get_path(b"lists") {
+        Ok(base) => chiaki::ListStore::new(base),
+        Err(_) => chiaki::ListStore::alongside_executable()?,
+    };
     
     // -------------------- feeds --------------------
     
     println!("Loading feeds...");
     
-    let feeds = chiaki::List::load("feeds")?;
+    let feeds = lists.load("feeds")?;
+    
+    for entry in feeds.collisions() {
+        println!("Ignoring feed '{}' colliding with an earlier one", String::from_utf8_lossy(entry.tag));
+    }
     
     // -------------------- rules --------------------
     
     println!("Loading rules...");
     
-    let mut rules = chiaki::List::load("rules")?;
+    let mut rules = lists.load("rules")?;
+    
+    for entry in rules.collisions() {
+        println!("Ignoring rule '{}' colliding with an earlier one", String::from_utf8_lossy(entry.tag));
+    }
+    
+    // -------------------- cache --------------------
+    
+    println!("Loading cache...");
+    
+    let mut cache = akari::Cache::load(env::current_exe()?.with_extension("cache"))?;
     
     // -------------------- client --------------------
     
-    let mut client = akari::Client::new()?;
+    let mut builder = akari::Client::builder();
+    
+    if let Some(proxy) = proxy {
+        builder = builder.with_proxy(proxy);
+    }
+    
+    if let Some(user_agent) = user_agent {
+        builder = builder.with_user_agent(user_agent);
+    }
+    
+    // shared by every client built from the same configuration
+    let cookie_jar = cookies.map(akari::CookieJar::load).transpose()?;
+    
+    if let Some(cookie_jar) = &cookie_jar {
+        builder = builder.with_cookie_jar(cookie_jar.clone());
+    }
+    
+    let mut client = builder.clone().build()?;
+    
+    // -------------------- fetch --------------------
+    
+    println!("Fetching feeds...");
+    
+    let urls: Vec<&str> = feeds.iter()
+        .filter_map(|feed| str::from_utf8(feed.tag).ok())
+        .collect();
+    
+    // feeds are fetched concurrently, while releases are processed in order
+    let fetched = akari::Batch::new(builder).run(&urls, |client, url| {
+        
+        let payload = client.request(url)
+            .with_cache(&cache)
+            .send()?;
+        
+        if payload.unchanged() {
+            return Ok(None);
+        }
+        
+        let validators = payload.validators();
+        
+        Ok(Some((Releases::new(payload)?, validators)))
+        
+    });
     
     // -------------------- releases --------------------
     
-    for url in feeds.iter().filter_map(|feed| str::from_utf8(feed.tag).ok()) {
+    for (url, fetched) in urls.into_iter().zip(fetched) {
         
         println!();
         println!("{}", url);
         println!("--------------------");
         
-        for release in Releases::new(&mut client, url)?.iter() {
+        let feed = akari::Url::parse(url)?;
+        
+        let Some((releases, validators)) = fetched? else {
+            println!("No changes");
+            continue;
+        };
+        
+        for release in releases.iter() {
             
             // -------------------- rule and episode --------------------
             
-            let Some(rule) = rules.iter().find(|rule| release.title.starts_with(rule.tag)) else {
+            // the most specific rule wins
+            let Some((rule, matched)) = rules.longest_prefix_of(release.title) else {
                 continue;
             };
             
-            let Some(episode) = extract_episode(&release, &rule) else {
+            // copied to release the borrow on the rules before updating them
+            let rule_tag = rule.tag.to_vec();
+            
+            let Some(episode) = extract_episode(&release.title[matched..]) else {
                 continue;
             };
             
@@ -91,24 +183,36 @@ fn process() -> Result<(), Box<dyn Error>> {
                 continue;
             };
             
+            // links might be relative to the feed
+            let Ok(link) = feed.join(link) else {
+                continue;
+            };
+            
             // -------------------- download and update --------------------
             
             println!("{}", title);
             
-            download_torrent(&mut client, link, &build_destination(folder, title)?)?;
+            download_torrent(&mut client, &link.to_string(), &build_destination(folder, title)?)?;
             
-            // used release title instead of rule tag to avoid borrowing error
-            rules.update(&release.title[..rule.tag.len()], episode)?;
+            rules.update(&rule_tag, episode)?;
             
         }
         
+        // only stored after every relevant release has been processed
+        cache.insert(url, validators)?;
+        
+    }
+    
+    // -------------------- cookies --------------------
+    
+    if let Some(cookie_jar) = cookie_jar {
+        cookie_jar.save()?;
     }
     
     Ok(())
 }
 
-fn extract_episode(release: &ReleasesEntry, rule: &chiaki::ListEntry) -> Option<u64> {
-    let clean = &release.title[rule.tag.len()..];
+fn extract_episode(clean: &[u8]) -> Option<u64> {
     let mut chars = clean.iter().copied().map(char::from);
     let mut episode = chars.find_map(|current| current.to_digit(10).map(u64::from))?;
     
@@ -119,9 +223,9 @@ fn extract_episode(release: &ReleasesEntry, rule: &chiaki::ListEntry) -> Option<
     Some(episode)
 }
 
-fn build_destination(folder: &str, title: &str) -> Result<PathBuf, Box<dyn Error>> {
+fn build_destination(folder: &Path, title: &str) -> Result<PathBuf, Box<dyn Error>> {
     let filename = Path::new(title).file_name().ok_or("Invalid file name")?;
-    let mut destination = Path::new(folder).join(filename);
+    let mut destination = folder.join(filename);
     
     if let Some(current) = destination.extension() {
         if ! current.eq_ignore_ascii_case("torrent") {
@@ -138,18 +242,16 @@ fn build_destination(folder: &str, title: &str) -> Result<PathBuf, Box<dyn Error
 }
 
 fn download_torrent(client: &mut akari::Client, link: &str, destination: &Path) -> Result<(), Box<dyn Error>> {
-    let mut payload = client.get(link)?;
-    
-    let file = fs::OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(destination)?;
+    let result = client.request(link)
+        .with_size_limit(TORRENT_FILE_SIZE_LIMIT)
+        .download(destination);
     
-    let mut writer = BufWriter::with_capacity(TORRENT_FILE_WRITER_BUFFER_SIZE, file);
-    
-    io::copy(&mut payload, &mut writer)?;
+    // partial files are kept to be resumed on the next run, unless their content cannot be trusted
+    if result.as_ref().is_err_and(|error| error.kind() == io::ErrorKind::InvalidData) {
+        fs::remove_file(destination).ok();
+    }
     
-    writer.flush()?;
+    result?;
     
     Ok(())
 }
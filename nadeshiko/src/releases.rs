@@ -1,7 +1,4 @@
-use std::{
-    error::Error,
-    io::Read,
-};
+use std::io::{ self, Read };
 
 pub struct Releases {
     content: Vec<u8>,
@@ -25,9 +22,7 @@ const LINK_CLOSE_TAG: &[u8] = b"</link>";
 
 impl Releases {
     
-    pub fn new(client: &mut akari::Client, url: &str) -> Result<Self, Box<dyn Error>> {
-        let mut payload = client.get(url)?;
-        
+    pub fn new(mut payload: akari::Payload) -> io::Result<Self> {
         let mut content = Vec::with_capacity(payload.content_length());
         payload.read_to_end(&mut content)?;
         
@@ -36,7 +31,7 @@ impl Releases {
         })
     }
     
-    pub fn iter(&self) -> ReleasesIter {
+    pub fn iter(&self) -> ReleasesIter<'_> {
         ReleasesIter {
             rest: &self.content,
         }
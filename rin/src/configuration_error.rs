@@ -0,0 +1,26 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+// every missing or invalid parameter found while registering a schema
+#[derive(Debug)]
+pub struct ConfigurationError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ConfigurationError {
+    
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid configuration file")?;
+        
+        for problem in &self.problems {
+            write!(f, "\n- {}", problem)?;
+        }
+        
+        Ok(())
+    }
+    
+}
+
+impl Error for ConfigurationError {}
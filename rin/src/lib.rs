@@ -1,32 +1,218 @@
+mod configuration_error;
+mod parser;
+mod schema;
+mod source;
+mod syntax_error;
+mod watcher;
+
 use std::{
-    env,
     error::Error,
-    fs,
-    str,
-    sync::OnceLock,
+    path::Path,
+    sync::{ Mutex, OnceLock, RwLock },
+    time::Duration,
 };
 
-pub fn get(key: &[u8]) -> Result<&'static str, Box<dyn Error>> {
-    let content = load();
+pub use configuration_error::ConfigurationError;
+pub use schema::{ Schema, Kind };
+pub use source::Source;
+pub use syntax_error::SyntaxError;
+
+use source::Setting;
+
+type Callback = Box<dyn Fn(&[String]) + Send>;
+
+static SCHEMA: OnceLock<Schema> = OnceLock::new();
+
+// every generation is leaked, as values handed out previously might still be referenced
+static SETTINGS: RwLock<Option<&'static [Setting]>> = RwLock::new(None);
+
+static CALLBACKS: Mutex<Vec<Callback>> = Mutex::new(Vec::new());
+
+
+// -------------------- loading --------------------
+
+
+// triggered by the first access to a parameter otherwise, a failed attempt being retried on the next one
+pub fn load() -> Result<(), Box<dyn Error>> {
+    settings().map(|_| ())
+}
+
+// expected once at startup, before any parameter is accessed, and reporting every problem at once
+pub fn register(schema: Schema) -> Result<(), Box<dyn Error>> {
+    let problems = validate(&schema, settings()?, &source::unvalued_flags()?);
+    
+    SCHEMA.set(schema).map_err(|_| "Configuration schema already registered")?;
+    
+    if ! problems.is_empty() {
+        return Err(ConfigurationError { problems }.into());
+    }
+    
+    Ok(())
+}
+
+// the current parameters are kept if the new ones fail to load or to satisfy the registered schema, returns the keys whose value changed
+pub fn reload() -> Result<Vec<String>, Box<dyn Error>> {
+    let settings = source::collect()?;
+    
+    if let Some(schema) = SCHEMA.get() {
+        let problems = validate(schema, &settings, &source::unvalued_flags()?);
+        if ! problems.is_empty() {
+            return Err(ConfigurationError { problems }.into());
+        }
+    }
+    
+    let settings: &'static [Setting] = Box::leak(settings.into_boxed_slice());
+    
+    let previous = SETTINGS.write()
+        .unwrap_or_else(|error| error.into_inner())
+        .replace(settings)
+        .unwrap_or_default();
+    
+    let changed = changed_keys(previous, settings);
     
-    if let Some(range) = chikuwa::subslice_range(content, key, b"\r\n") {
-        if let [b' ', b'=', b' ', value @ ..] = &content[range] {
-            return Ok(str::from_utf8(value)?);
+    if ! changed.is_empty() {
+        for callback in CALLBACKS.lock().unwrap_or_else(|error| error.into_inner()).iter() {
+            callback(&changed);
         }
     }
     
-    Err(format!("Missing or invalid field: '{}'", &String::from_utf8_lossy(key)).into())
+    Ok(changed)
+}
+
+// reloads the parameters whenever one of the files they might be read from is created, modified or deleted
+pub fn watch(interval: Duration) -> Result<(), Box<dyn Error>> {
+    watcher::start(interval)
+}
+
+// called with the keys whose value changed after every successful reload
+pub fn on_change<F: Fn(&[String]) + Send + 'static>(callback: F) {
+    CALLBACKS.lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .push(Box::new(callback));
+}
+
+
+// -------------------- getters --------------------
+
+
+// falls back to the default value of the registered schema, if any
+pub fn get(key: &[u8]) -> Result<&'static str, Box<dyn Error>> {
+    if let Some(setting) = find(settings()?, key) {
+        return Ok(&setting.value);
+    }
+    
+    SCHEMA.get()
+        .and_then(|schema| schema.default_value(key))
+        .ok_or_else(|| format!("Missing field: '{}'", &String::from_utf8_lossy(key)).into())
 }
 
-fn load() -> &'static [u8] {
-    static CONTENT: OnceLock<Vec<u8>> = OnceLock::new();
-    CONTENT.get_or_init(|| {
+pub fn get_u16(key: &[u8]) -> Result<u16, Box<dyn Error>> {
+    parsed(key, schema::parse_u16)
+}
+
+pub fn get_bool(key: &[u8]) -> Result<bool, Box<dyn Error>> {
+    parsed(key, schema::parse_bool)
+}
+
+pub fn get_path(key: &[u8]) -> Result<&'static Path, Box<dyn Error>> {
+    parsed(key, schema::parse_path)
+}
+
+pub fn get_list(key: &[u8]) -> Result<Vec<&'static str>, Box<dyn Error>> {
+    get(key).map(schema::parse_list)
+}
+
+pub fn get_duration(key: &[u8]) -> Result<Duration, Box<dyn Error>> {
+    parsed(key, schema::parse_duration)
+}
+
+// missing for parameters that are not set, nor given a default value by the registered schema
+pub fn source(key: &[u8]) -> Option<Source> {
+    match find(settings().ok()?, key) {
+        Some(setting) => Some(setting.source.clone()),
+        None => SCHEMA.get()
+            .and_then(|schema| schema.default_value(key))
+            .map(|_| Source::Default),
+    }
+}
+
+
+// -------------------- helpers --------------------
+
+
+fn parsed<T>(key: &[u8], parse: fn(&'static str) -> Result<T, String>) -> Result<T, Box<dyn Error>> {
+    parse(get(key)?).map_err(|reason| {
+        let source = source(key).unwrap_or(Source::Default);
+        format!("Invalid field: '{}' ({}), from {}", String::from_utf8_lossy(key), reason, source).into()
+    })
+}
+
+fn settings() -> Result<&'static [Setting], Box<dyn Error>> {
+    if let Some(settings) = *SETTINGS.read().unwrap_or_else(|error| error.into_inner()) {
+        return Ok(settings);
+    }
+    
+    let mut current = SETTINGS.write().unwrap_or_else(|error| error.into_inner());
+    
+    // another thread might have loaded them in the meantime
+    if let Some(settings) = *current {
+        return Ok(settings);
+    }
+    
+    let settings: &'static [Setting] = Box::leak(source::collect()?.into_boxed_slice());
+    
+    *current = Some(settings);
+    
+    Ok(settings)
+}
+
+fn find<'s>(settings: &'s [Setting], key: &[u8]) -> Option<&'s Setting> {
+    settings.iter().find(|setting| setting.key.as_bytes().eq_ignore_ascii_case(key))
+}
+
+fn validate(schema: &Schema, settings: &[Setting], unvalued: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    
+    for parameter in schema.parameters() {
         
-        let path = env::current_exe()
-            .expect("Failed to get executable name")
-            .with_extension("rn");
+        let key = String::from_utf8_lossy(parameter.key);
+        let setting = find(settings, parameter.key);
         
-        fs::read(&path).unwrap_or_else(|_| panic!("Load of configuration file located at '{}' failed", &path.to_string_lossy()))
+        if unvalued.iter().any(|flag| flag.as_bytes().eq_ignore_ascii_case(parameter.key)) {
+            problems.push(format!("Missing value of command-line flag '--{}'", key.replace('_', "-")));
+            continue;
+        }
         
-    })
+        let value = match (setting, parameter.default) {
+            (Some(setting), _) => setting.value.as_str(),
+            (None, Some(default)) => default,
+            (None, None) if parameter.required => {
+                problems.push(format!("Missing field: '{}'", key));
+                continue;
+            },
+            (None, None) => continue,
+        };
+        
+        if let Err(reason) = parameter.kind.validate(value) {
+            let source = setting.map_or(Source::Default, |setting| setting.source.clone());
+            problems.push(format!("Invalid field: '{}' ({}), from {}", key, reason, source));
+        }
+        
+    }
+    
+    problems
+}
+
+// in lowercase, including keys that were either added or removed
+fn changed_keys(previous: &[Setting], current: &[Setting]) -> Vec<String> {
+    let mut changed: Vec<String> = previous.iter()
+        .chain(current)
+        .filter(|setting| find(previous, setting.key.as_bytes()).map(|setting| &setting.value) != find(current, setting.key.as_bytes()).map(|setting| &setting.value))
+        .map(|setting| setting.key.to_ascii_lowercase())
+        .collect();
+    
+    changed.sort();
+    changed.dedup();
+    
+    changed
 }
@@ -0,0 +1,91 @@
+use std::str;
+
+use super::SyntaxError;
+
+// one "key = value" pair per line, ending with either "\n" or "\r\n", blank lines and lines starting with "#" being ignored
+// keys are matched ignoring ASCII case and cannot be repeated, values are trimmed unless quoted
+pub fn parse(content: &[u8]) -> Result<Vec<(String, String)>, SyntaxError> {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+    
+    let mut parameters: Vec<(String, String, usize)> = Vec::new();
+    
+    for (index, line) in content.split(|&byte| byte == b'\n').enumerate() {
+        
+        let number = index + 1;
+        
+        let error = |reason: &str| SyntaxError { line: number, reason: reason.to_owned() };
+        
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = str::from_utf8(line).map_err(|_| error("Invalid UTF-8"))?.trim();
+        
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        
+        // -------------------- key --------------------
+        
+        let (key, value) = line.split_once('=').ok_or_else(|| error("Expected 'key = value'"))?;
+        let key = key.trim();
+        
+        if key.is_empty() {
+            return Err(error("Missing key"));
+        }
+        
+        if key.contains(|current: char| current.is_whitespace() || current == '"') {
+            return Err(error("Invalid key"));
+        }
+        
+        if let Some((_, _, first)) = parameters.iter().find(|(current, _, _)| current.eq_ignore_ascii_case(key)) {
+            return Err(error(&format!("Duplicate key '{}', first defined at line {}", key, first)));
+        }
+        
+        // -------------------- value --------------------
+        
+        let value = value.trim();
+        
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => unquote(quoted).map_err(error)?,
+            None => value.to_owned(),
+        };
+        
+        parameters.push((key.to_owned(), value, number));
+        
+    }
+    
+    Ok(parameters.into_iter()
+        .map(|(key, value, _)| (key, value))
+        .collect())
+}
+
+// expects the opening quote to be stripped already, with nothing being allowed after the closing one
+fn unquote(quoted: &str) -> Result<String, &'static str> {
+    let mut value = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    
+    loop {
+        match chars.next() {
+            
+            None => return Err("Unterminated quoted value"),
+            
+            Some('"') => break,
+            
+            Some('\\') => value.push(match chars.next() {
+                Some('\\') => '\\',
+                Some('"') => '"',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                _ => return Err("Unknown escape sequence in quoted value"),
+            }),
+            
+            Some(current) => value.push(current),
+            
+        }
+    }
+    
+    if ! chars.as_str().trim().is_empty() {
+        return Err("Unexpected characters after quoted value");
+    }
+    
+    Ok(value)
+}
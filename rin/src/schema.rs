@@ -0,0 +1,137 @@
+use std::{
+    path::Path,
+    time::Duration,
+};
+
+// parameters expected by a binary, validated all at once when registered
+#[derive(Default)]
+pub struct Schema {
+    parameters: Vec<Parameter>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Text,
+    U16,
+    // "true", "yes", "on" or "1" and "false", "no", "off" or "0", ignoring case
+    Bool,
+    Path,
+    // comma-separated, surrounding whitespace and empty items being dropped
+    List,
+    // a number of milliseconds, seconds, minutes or hours (e.g. "500ms", "30s", "5m", "2h"), seconds if no unit is given
+    Duration,
+}
+
+pub(crate) struct Parameter {
+    pub(crate) key: &'static [u8],
+    pub(crate) kind: Kind,
+    pub(crate) required: bool,
+    pub(crate) default: Option<&'static str>,
+}
+
+impl Schema {
+    
+    // -------------------- constructors --------------------
+    
+    
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_required(self, key: &'static [u8], kind: Kind) -> Self {
+        self.with_parameter(key, kind, true, None)
+    }
+    
+    pub fn with_optional(self, key: &'static [u8], kind: Kind) -> Self {
+        self.with_parameter(key, kind, false, None)
+    }
+    
+    // used whenever the parameter is missing from the file
+    pub fn with_default(self, key: &'static [u8], kind: Kind, default: &'static str) -> Self {
+        self.with_parameter(key, kind, false, Some(default))
+    }
+    
+    
+    // -------------------- accessors --------------------
+    
+    
+    pub(crate) fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+    
+    pub(crate) fn default_value(&self, key: &[u8]) -> Option<&'static str> {
+        self.parameters.iter()
+            .find(|parameter| parameter.key.eq_ignore_ascii_case(key))
+            .and_then(|parameter| parameter.default)
+    }
+    
+    
+    // -------------------- helpers --------------------
+    
+    
+    fn with_parameter(mut self, key: &'static [u8], kind: Kind, required: bool, default: Option<&'static str>) -> Self {
+        self.parameters.push(Parameter { key, kind, required, default });
+        self
+    }
+    
+}
+
+impl Kind {
+    
+    pub(crate) fn validate(self, value: &str) -> Result<(), String> {
+        match self {
+            Self::Text | Self::List => Ok(()),
+            Self::U16 => parse_u16(value).map(|_| ()),
+            Self::Bool => parse_bool(value).map(|_| ()),
+            Self::Path => parse_path(value).map(|_| ()),
+            Self::Duration => parse_duration(value).map(|_| ()),
+        }
+    }
+    
+}
+
+pub(crate) fn parse_u16(value: &str) -> Result<u16, String> {
+    value.trim()
+        .parse()
+        .map_err(|_| format!("expected a number between 0 and {}", u16::MAX))
+}
+
+pub(crate) fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(String::from("expected a boolean")),
+    }
+}
+
+pub(crate) fn parse_path(value: &str) -> Result<&Path, String> {
+    if value.trim().is_empty() {
+        return Err(String::from("expected a path"));
+    }
+    
+    Ok(Path::new(value))
+}
+
+pub(crate) fn parse_list(value: &str) -> Vec<&str> {
+    value.split(',')
+        .map(str::trim)
+        .filter(|item| ! item.is_empty())
+        .collect()
+}
+
+pub(crate) fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    
+    let split = value.find(|current: char| ! current.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    
+    let amount: u64 = amount.parse().map_err(|_| String::from("expected a duration"))?;
+    
+    match unit.trim() {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "" | "s" => Ok(Duration::from_secs(amount)),
+        "m" => amount.checked_mul(60).map(Duration::from_secs).ok_or_else(|| String::from("duration too large")),
+        "h" => amount.checked_mul(3600).map(Duration::from_secs).ok_or_else(|| String::from("duration too large")),
+        unit => Err(format!("unknown duration unit '{}'", unit)),
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    env,
+    error::Error,
+    ffi::OsString,
+    fmt,
+    fs,
+    io,
+    path::{ Path, PathBuf },
+};
+
+use super::parser;
+
+// where the effective value of a parameter came from
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Source {
+    Flag(String),
+    Environment(String),
+    File(PathBuf),
+    Default,
+}
+
+// files along with whether they are required to exist, lowest priority first
+struct Invocation {
+    name: String,
+    files: Vec<(PathBuf, bool)>,
+    flags: Vec<Setting>,
+}
+
+// flags given without a value are only reported for keys in the schema, other binaries' arguments being none of its business
+struct Arguments {
+    config: Option<PathBuf>,
+    flags: Vec<Setting>,
+    unvalued: Vec<String>,
+}
+
+pub(crate) struct Setting {
+    pub(crate) key: String,
+    pub(crate) value: String,
+    pub(crate) source: Source,
+}
+
+impl fmt::Display for Source {
+    
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Flag(flag) => write!(f, "command-line flag '{}'", flag),
+            Self::Environment(variable) => write!(f, "environment variable '{}'", variable),
+            Self::File(path) => write!(f, "file '{}'", path.to_string_lossy()),
+            Self::Default => write!(f, "default value"),
+        }
+    }
+    
+}
+
+// lowest priority first, every source overriding the parameters of the previous ones
+// files alongside the executable, in XDG locations and given through "--config", then environment variables and command-line flags
+pub(crate) fn collect() -> Result<Vec<Setting>, Box<dyn Error>> {
+    let Invocation { name, files, flags } = invocation()?;
+    
+    let mut settings = Vec::new();
+    
+    // -------------------- files --------------------
+    
+    for (path, required) in files {
+        match fs::read(&path) {
+            Ok(content) => merge(&mut settings, parse_file(&path, &content)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound && ! required => (),
+            Err(error) => return Err(format!("Load of configuration file located at '{}' failed: {}", path.to_string_lossy(), error).into()),
+        }
+    }
+    
+    // -------------------- environment --------------------
+    
+    // named after the executable and the key, in uppercase (e.g. "MINAMI_ROOT")
+    let prefix = format!("{}_", variable_name(&name));
+    
+    let variables = env::vars_os()
+        .filter_map(|(variable, value)| Some((variable.into_string().ok()?, value.into_string().ok()?)))
+        .filter_map(|(variable, value)| {
+            let key = variable.to_ascii_uppercase().strip_prefix(&prefix)?.to_ascii_lowercase();
+            (! key.is_empty()).then_some(Setting { key, value, source: Source::Environment(variable) })
+        })
+        .collect();
+    
+    merge(&mut settings, variables);
+    
+    // -------------------- flags --------------------
+    
+    merge(&mut settings, flags);
+    
+    Ok(settings)
+}
+
+// keys of the "--key" flags given without a value
+pub(crate) fn unvalued_flags() -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(arguments(env::args_os().skip(1))?.unvalued)
+}
+
+// every file parameters might be read from, lowest priority first
+pub(crate) fn files() -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    Ok(invocation()?.files.into_iter()
+        .map(|(path, _)| path)
+        .collect())
+}
+
+fn invocation() -> Result<Invocation, Box<dyn Error>> {
+    let executable = env::current_exe()?;
+    
+    let name = executable.file_stem()
+        .ok_or("Failed to get executable name")?
+        .to_string_lossy()
+        .into_owned();
+    
+    let Arguments { config, flags, .. } = arguments(env::args_os().skip(1))?;
+    
+    let mut files = vec![(executable.with_extension("rn"), false)];
+    
+    files.extend(xdg_files(&name).into_iter().map(|path| (path, false)));
+    
+    // unlike the others, an explicitly given file has to exist
+    files.extend(config.map(|path| (path, true)));
+    
+    Ok(Invocation { name, files, flags })
+}
+
+// "--key=value" flags, along with the path given by either "--config=path" or "--config path", any other argument being skipped
+fn arguments(arguments: impl Iterator<Item = OsString>) -> Result<Arguments, Box<dyn Error>> {
+    let mut config = None;
+    let mut flags = Vec::new();
+    let mut unvalued = Vec::new();
+    let mut arguments = arguments.peekable();
+    
+    while let Some(argument) = arguments.next() {
+        
+        let Some(flag) = argument.to_str().and_then(|argument| argument.strip_prefix("--")) else {
+            continue;
+        };
+        
+        // values are left out of the source, as they might be secrets
+        let (name, key, value) = match flag.split_once('=') {
+            Some((key, value)) => (format!("--{}", key), key.replace('-', "_"), value.to_owned()),
+            None if flag == "config" => {
+                let path = arguments.next().ok_or("Missing path after command-line flag '--config'")?;
+                config = Some(PathBuf::from(path));
+                continue;
+            },
+            None => {
+                unvalued.push(flag.replace('-', "_"));
+                continue;
+            },
+        };
+        
+        if key.eq_ignore_ascii_case("config") {
+            config = Some(PathBuf::from(value));
+            continue;
+        }
+        
+        flags.push(Setting {
+            key,
+            value,
+            source: Source::Flag(name),
+        });
+        
+    }
+    
+    Ok(Arguments { config, flags, unvalued })
+}
+
+// "<name>/<name>.rn" within the XDG configuration directories, lowest priority first
+fn xdg_files(name: &str) -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = env::var_os("XDG_CONFIG_DIRS")
+        .map(|directories| env::split_paths(&directories).collect())
+        .unwrap_or_default();
+    
+    directories.reverse();
+    
+    let home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    
+    directories.extend(home);
+    
+    // relative paths are to be ignored as per the specification
+    directories.into_iter()
+        .filter(|directory| directory.is_absolute())
+        .map(|directory| directory.join(name).join(name).with_extension("rn"))
+        .collect()
+}
+
+fn parse_file(path: &Path, content: &[u8]) -> Result<Vec<Setting>, Box<dyn Error>> {
+    let parameters = parser::parse(content)
+        .map_err(|error| format!("Load of configuration file located at '{}' failed: {}", path.to_string_lossy(), error))?;
+    
+    Ok(parameters.into_iter()
+        .map(|(key, value)| Setting { key, value, source: Source::File(path.to_owned()) })
+        .collect())
+}
+
+// keys are matched ignoring ASCII case, the later settings winning
+fn merge(settings: &mut Vec<Setting>, overrides: Vec<Setting>) {
+    for setting in overrides {
+        settings.retain(|current| ! current.key.eq_ignore_ascii_case(&setting.key));
+        settings.push(setting);
+    }
+}
+
+fn variable_name(name: &str) -> String {
+    name.chars()
+        .map(|current| if current.is_ascii_alphanumeric() { current.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
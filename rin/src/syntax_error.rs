@@ -0,0 +1,21 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+#[derive(Debug)]
+pub struct SyntaxError {
+    // starting from one
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for SyntaxError {
+    
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid configuration file at line {}: {}", self.line, self.reason)
+    }
+    
+}
+
+impl Error for SyntaxError {}
@@ -0,0 +1,50 @@
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    sync::atomic::{ AtomicBool, Ordering },
+    thread,
+    time::{ Duration, SystemTime },
+};
+
+use super::source;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+// polls the modification time and size of every file, as a change notification API would be platform-specific
+pub fn start(interval: Duration) -> Result<(), Box<dyn Error>> {
+    let files = source::files()?;
+    
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return Err("Configuration already watched".into());
+    }
+    
+    let mut current = fingerprint(&files);
+    
+    thread::Builder::new()
+        .name(String::from("rin-watcher"))
+        .spawn(move || loop {
+            
+            thread::sleep(interval);
+            
+            let latest = fingerprint(&files);
+            
+            if latest == current {
+                continue;
+            }
+            
+            current = latest;
+            
+            // a failed reload keeps the current parameters, waiting for the files to change again
+            super::reload().ok();
+            
+        })?;
+    
+    Ok(())
+}
+
+fn fingerprint(files: &[PathBuf]) -> Vec<Option<(SystemTime, u64)>> {
+    files.iter()
+        .map(|path| fs::metadata(path).and_then(|metadata| Ok((metadata.modified()?, metadata.len()))).ok())
+        .collect()
+}